@@ -0,0 +1,67 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use fc::cpu::decode_cache::DecodeCache;
+use fc::cpu::memory_map::MemoryMap;
+use fc::cpu::Cpu;
+use fc::{ines, io};
+
+/// 1回の計測で実行する命令数
+const STEPS: u64 = 10_000;
+
+/// docs/demo/sample1.nes
+/// HELLO, WORLD!を書き込んだあとはmainloopで無限ループする
+fn sample1() -> Cpu {
+    let mut contents = io::read_to_binary("../docs/demo/sample1.nes").unwrap();
+    let ines = ines::parser(&mut contents).unwrap();
+    Cpu::new(ines)
+}
+
+/// 止まらずに計算を続けるテストROM
+/// $0200～$02FFの合計を求めて$00に書き込み、それを$0200～$02FFへ足し込むのを繰り返す。
+fn checksum() -> Cpu {
+    let code = [
+        0xa2, 0x00, // LDX #$00
+        0x8a, // TXA
+        0x18, // CLC
+        0x7d, 0x00, 0x02, // ADC $0200,X
+        0xe8, // INX
+        0xd0, 0xf9, // BNE $8003
+        0x85, 0x00, // STA $00
+        0xa2, 0x00, // LDX #$00
+        0xbd, 0x00, 0x02, // LDA $0200,X
+        0x65, 0x00, // ADC $00
+        0x9d, 0x00, 0x02, // STA $0200,X
+        0xe8, // INX
+        0xd0, 0xf5, // BNE $800E
+        0x4c, 0x00, 0x80, // JMP $8000
+    ];
+    let mut program = vec![0xea; 0x8000];
+    program[..code.len()].copy_from_slice(&code);
+    let mut cpu = Cpu::with_bus(MemoryMap::new(program));
+    cpu.register.PC = 0x8000;
+    cpu
+}
+
+fn decode_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_cache");
+    group.throughput(Throughput::Elements(STEPS));
+    for (rom, cpu) in [("sample1", sample1 as fn() -> Cpu), ("checksum", checksum)] {
+        for (label, cache) in [
+            ("cached", DecodeCache::default()),
+            ("uncached", DecodeCache::new(0)),
+        ] {
+            group.bench_function(format!("{}/{}", rom, label), |b| {
+                let mut cpu = cpu();
+                cpu.decode_cache = cache.clone();
+                b.iter(|| {
+                    for _ in 0..STEPS {
+                        black_box(cpu.step().unwrap());
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode_cache);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use fc::cpu::memory_map::MemoryMap;
+use fc::cpu::Cpu;
+use fc::ppu;
+
+/// 1回の計測で実行する命令数
+const STEPS: u64 = 10_000;
+
+/// WRAM、PPUレジスタ、PRG-ROMを満遍なく読み書きするループ
+fn cpu() -> Cpu {
+    let code = [
+        0xbd, 0x00, 0x02, // LDA $0200,X
+        0x9d, 0x00, 0x03, // STA $0300,X
+        0xad, 0x02, 0x20, // LDA $2002
+        0x8d, 0x06, 0x20, // STA $2006
+        0xb5, 0x10, // LDA $10,X
+        0xe8, // INX
+        0xd0, 0xf0, // BNE $8000
+        0x4c, 0x00, 0x80, // JMP $8000
+    ];
+    let mut program = vec![0xea; 0x8000];
+    program[..code.len()].copy_from_slice(&code);
+    let mut cpu = Cpu::with_bus(MemoryMap::new(program));
+    cpu.register.PC = 0x8000;
+    cpu
+}
+
+fn cpu_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cpu");
+    group.throughput(Throughput::Elements(STEPS));
+    group.bench_function("step", |b| {
+        let mut cpu = cpu();
+        b.iter(|| {
+            for _ in 0..STEPS {
+                black_box(cpu.step().unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn memory_map(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memory_map");
+    group.throughput(Throughput::Elements(0x10000));
+    group.bench_function("cpu_read", |b| {
+        let mut memory = MemoryMap::new(vec![0xea; 0x8000]);
+        b.iter(|| {
+            for addr in 0..=0xffff {
+                let _ = black_box(memory.read(addr));
+            }
+        })
+    });
+    group.throughput(Throughput::Elements(0x4000));
+    group.bench_function("ppu_read", |b| {
+        let memory = ppu::memory_map::MemoryMap::new();
+        b.iter(|| {
+            for addr in 0..0x4000 {
+                black_box(memory.read(addr));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, cpu_step, memory_map);
+criterion_main!(benches);
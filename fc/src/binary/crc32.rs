@@ -0,0 +1,22 @@
+/// CRC-32(IEEE 802.3)
+/// ROMの識別に使われるハッシュで、No-IntroやFCEUXが表示する値と同じです。
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for b in data.iter() {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[test]
+fn it_crc32() {
+    assert_eq!(crc32(b""), 0x0000_0000);
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+}
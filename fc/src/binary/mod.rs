@@ -1,7 +1,9 @@
 mod bit;
+mod crc32;
 mod display_binary;
 mod util;
 
 pub use bit::Bit;
+pub use crc32::crc32;
 pub use display_binary::DisplayBinary;
 pub use util::{lower_only, u16_to_u8u8, u8u8_to_u16, upper_only};
@@ -0,0 +1,31 @@
+use crate::error::EmuError;
+
+/// CPUから見たアドレス空間
+/// Cpuはこれを通してのみメモリやI/Oレジスタにアクセスする。
+pub trait Bus {
+    /// CPUの読み込み
+    /// I/Oレジスタは読み込みによって状態が変わることがあります。
+    /// 何もつながっていないアドレスはEmuError::OpenBusを返し、CPUがデータバスに残っている値を使います。
+    fn read(&mut self, addr: u16) -> Result<u8, EmuError>;
+    /// CPUの書き込み
+    /// ROMへの書き込みなど実機では無視される書き込みはErrを返します。
+    fn write(&mut self, addr: u16, data: u8) -> Result<(), EmuError>;
+    /// 状態を変えずに値を覗き見ます。
+    /// トレーサーやデバッガが使います。
+    fn peek(&self, addr: u16) -> u8;
+    /// 命令をキャッシュしてよいアドレスなら、そこに見えている内容のバンクを返します。
+    /// 読み込みに副作用がなく、書き込みかバンク切り替えでしか内容が変わらない領域が対象です。
+    /// バンク切り替えで別の内容が見えるようになったら、別の値を返してください。
+    /// ミラーのある書き込み可能な領域は、書き込みを追えないのでNoneを返してください。
+    fn bank(&self, _addr: u16) -> Option<u32> {
+        None
+    }
+    /// CPUの1サイクルごとに呼び出され、CPUと同じクロックで動く機器を進めます。
+    /// 読み書きのあるサイクルでは、読み書きの前に呼び出されます。
+    fn tick(&mut self) {}
+    /// CPUのNMI信号線につながっている出力
+    /// Cpuは命令の前にこれを読み込み、立ち上がりでNMIを発生させます。
+    fn nmi(&self) -> bool {
+        false
+    }
+}
@@ -1,85 +1,330 @@
+use super::bus::Bus;
+use super::decode_cache::DecodeCache;
+use super::execution_record::ExecutionRecord;
+use super::history::History;
+use super::interrupt::Interrupt;
 use super::memory_map::MemoryMap;
+use super::power_on::PowerOnPolicy;
 use super::register::Register;
 use super::status_register::{flag_n, flag_z, StatusRegister};
 use crate::binary;
-use crate::ines::program::{Command, Mode, Operand, Program};
+use crate::error::{EmuError, Fault, FaultPolicy};
+use crate::ines::program::{
+    next_in_page, zero_page_indexed, Command, Mode, Operand, OrderSets, Program,
+};
+use crate::ines::sprite::Sprite;
 use crate::ines::INES;
-use crate::ppu::io_register::IORegister;
+use crate::ppu::io_register::OAMDATA_INDEX;
+use crate::ppu::PPU;
+use crate::scheduler::Scheduler;
 
-// const InitializeProgramCounter: usize = 0xFFFC;
+/// 割り込みシーケンスにかかるクロックサイクル数
+const INTERRUPT_CLOCK: usize = 7;
+
+/// OAM DMA
+/// 書き込んだ値を上位バイトとするページ$XX00～$XXFFを、PPUのOAMへ転送する。
+const OAMDMA: u16 = 0x4014;
 
 #[derive(PartialEq, Eq, Debug)]
-struct Cpu {
+pub struct Cpu<B = MemoryMap> {
     pub register: Register,
-    pub memory: MemoryMap,
+    pub bus: B,
+    /// NMI信号線の現在の状態
+    nmi_line: bool,
+    /// NMIはエッジトリガなので、立ち上がりを検出したら次の命令の前に処理する
+    nmi_pending: bool,
+    /// IRQ信号線の現在の状態
+    /// IRQはレベルトリガなので、アクティブな間はIフラグがクリアされるたびに発生する
+    irq_line: bool,
+    /// JAM命令で停止しているか
+    /// RESETされるまで割り込みも受け付けない。
+    jammed: bool,
+    /// 電源投入からの累計クロックサイクル数
+    pub cycles: u64,
+    /// Bus::tickでバスを進めたサイクル数
+    /// 命令の内部処理など、読み書きのないサイクルの分は命令の後でcyclesに追いつかせる。
+    bus_cycles: u64,
+    /// 最後にデータバスに乗った値
+    /// オープンバスの読み込みではこの値が見える。
+    data_bus: u8,
+    /// EmuErrorが起きたときの振る舞い
+    pub fault_policy: FaultPolicy,
+    /// 電源投入時のWRAMとレジスタの内容
+    pub power_on: PowerOnPolicy,
+    /// 実行中の命令のアドレス
+    current: u16,
+    /// 実行中の命令で起きた最初のFault
+    /// FaultPolicy::Breakのときだけ記録する。
+    fault: Option<Fault>,
+    /// 実行中の命令で読み込んだ(アドレス, 値)
+    reads: Vec<(u16, u8)>,
+    /// 実行中の命令で書き込んだ(アドレス, 値)
+    writes: Vec<(u16, u8)>,
+    /// 実行中の命令で$4014に書き込まれたページ
+    /// 命令が終わってからDMAを行う。
+    dma: Option<u8>,
+    /// 直近に実行した命令
+    /// Faultを返した命令も含む。
+    pub history: History,
+    /// デコード済みの命令
+    pub decode_cache: DecodeCache,
 }
 
 impl Cpu {
     pub fn new(ines: INES) -> Self {
-        let mut cpu = Cpu {
-            register: Register::new(),
-            memory: MemoryMap::new(ines.program_rom_data),
-        };
-        cpu.register.PC = 0x8000;
+        let mut bus = MemoryMap::new(ines.program_rom_data);
+        bus.scheduler = Scheduler::new(PPU::new(Sprite::parse_sprites(&ines.character_rom_data)));
+        let mut cpu = Cpu::with_bus(bus);
+        cpu.power_cycle();
         cpu
     }
 
-    pub fn run(&mut self) {
+    /// 電源を入れ直します。
+    /// WRAMとA、X、Yをpower_onに従って初期化し、PPUのレジスタやCPUの内部状態も電源投入時に戻してからRESETします。
+    /// WRAMを残したまま再起動する場合(リセットボタン)はresetを使ってください。
+    pub fn power_cycle(&mut self) {
+        let mut bytes = self.power_on.bytes();
+        self.bus.power_on(&mut bytes);
+        let mut register = Register::new();
+        register.A = bytes.next().unwrap_or_default();
+        register.X = bytes.next().unwrap_or_default();
+        register.Y = bytes.next().unwrap_or_default();
+        self.register = register;
+        self.nmi_line = false;
+        self.nmi_pending = false;
+        self.irq_line = false;
+        self.cycles = 0;
+        self.bus_cycles = 0;
+        self.data_bus = 0;
+        self.reset();
+    }
+
+    /// 1命令を実行し、実行内容やレジスタを標準出力に表示します。
+    /// かかったクロックサイクル数を返します。
+    pub fn run(&mut self) -> usize {
         // thread::sleep(time::Duration::from_millis(200));
-        let program = self.fetch_program();
         println!("==================================");
-        println!("[Program]{}", program);
-        let operand = self.update_operand_with_register(&program);
-        println!(
-            "[Exec]{:?} {} {}",
-            program.orderset.cmd, operand, program.orderset.clock,
-        );
-        self.exec(
-            program.orderset.cmd,
+        let cycles = match self.step() {
+            Ok(record) => {
+                println!("{}", record);
+                record.cycles
+            }
+            Err(fault) => {
+                println!("[Fault]{}", fault);
+                return 0;
+            }
+        };
+        println!("[PPURegister]\n{}", self.bus.ppu);
+        println!("[Stack]\n{}", self.bus.stack(self.register.SP));
+        // println!("[WRAM]\n{}", self.bus.wram());
+        cycles
+    }
+}
+
+impl<B: Bus> Cpu<B> {
+    /// バスを指定してCpuを作成します。RESETは行いません。
+    pub fn with_bus(bus: B) -> Self {
+        Cpu {
+            register: Register::new(),
+            bus,
+            nmi_line: false,
+            nmi_pending: false,
+            irq_line: false,
+            jammed: false,
+            cycles: 0,
+            bus_cycles: 0,
+            data_bus: 0,
+            fault_policy: FaultPolicy::default(),
+            power_on: PowerOnPolicy::default(),
+            current: 0,
+            fault: None,
+            reads: vec![],
+            writes: vec![],
+            dma: None,
+            history: History::default(),
+            decode_cache: DecodeCache::default(),
+        }
+    }
+
+    /// RESET(リセットボタン)
+    /// WRAMやA、X、Yはそのまま残る。
+    /// スタックへの書き込みは行わないが、プッシュと同じようにSPを3つ減らす。
+    /// Iフラグをセットし、PCを$FFFC、$FFFDからフェッチします。
+    pub fn reset(&mut self) {
+        self.register.SP = self.register.SP.wrapping_sub(3);
+        self.register.P = self.register.P.set_i(true);
+        self.register.PC = self.read_vector(Interrupt::RESET);
+        self.nmi_pending = false;
+        self.jammed = false;
+        self.cycles += INTERRUPT_CLOCK as u64;
+        self.catch_up();
+    }
+
+    /// NMI信号線の状態を更新します。
+    /// 非アクティブからアクティブに変わったときだけNMIが発生します。
+    pub fn set_nmi(&mut self, active: bool) {
+        if active && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = active;
+    }
+
+    /// IRQ信号線の状態を更新します。
+    pub fn set_irq(&mut self, active: bool) {
+        self.irq_line = active;
+    }
+
+    /// JAM命令で停止しているか
+    pub fn jammed(&self) -> bool {
+        self.jammed
+    }
+
+    /// 1命令を実行し、その内容を返します。
+    /// 命令の前に割り込みを処理した場合は、そのサイクル数やメモリアクセスも含みます。
+    /// FaultPolicy::Breakのときは、命令の実行中にEmuErrorが起きるとその命令を最後まで実行してからErrを返します。
+    pub fn step(&mut self) -> Result<&ExecutionRecord, Fault> {
+        self.reads.clear();
+        self.writes.clear();
+        self.fault = None;
+        let before = self.register;
+        let mut cycles = 0;
+        let interrupt = if self.jammed {
+            None
+        } else {
+            self.poll_interrupt()
+        };
+        if let Some(interrupt) = interrupt {
+            self.interrupt(interrupt);
+            cycles += INTERRUPT_CLOCK;
+        }
+        let addr = self.register.PC;
+        self.current = addr;
+        let (program, bytes, length) = self.fetch_program();
+        let (operand, page_crossed) = self.update_operand_with_register(&program);
+        cycles += self.exec(program.orderset.cmd, operand, program.orderset.clock);
+        if page_crossed && Self::has_page_cross_penalty(program.orderset.cmd) {
+            cycles += 1;
+        }
+        let dma = self.dma.take();
+        if let Some(page) = dma {
+            cycles += self.oam_dma(page, self.cycles + cycles as u64);
+        }
+        self.cycles += cycles as u64;
+        self.catch_up();
+        // 最後のサイクルの書き込みでNMIが有効になった場合も、次の命令の前に発生させる
+        let nmi = self.bus.nmi();
+        self.set_nmi(nmi);
+
+        let record = ExecutionRecord {
+            interrupt,
+            addr,
+            bytes,
+            length,
+            cmd: program.orderset.cmd,
+            mode: program.orderset.mode,
             operand,
-            program.orderset.clock as usize,
-        );
+            cycles,
+            reads: std::mem::take(&mut self.reads),
+            writes: std::mem::take(&mut self.writes),
+            dma,
+            before,
+            after: self.register,
+        };
+        let (record, evicted) = self.history.push(record);
+        // 捨てた命令のバッファを次の命令で使い回し、命令ごとに確保し直さない
+        if let Some(evicted) = evicted {
+            self.reads = evicted.reads;
+            self.writes = evicted.writes;
+            self.reads.clear();
+            self.writes.clear();
+        }
+        match self.fault.take() {
+            Some(fault) => Err(fault),
+            None => Ok(record),
+        }
+    }
 
-        println!("[CPURegister]\n{}", self.register);
-        println!("[PPURegister]\n{}", self.memory.ppu);
-        println!("[Stack]\n{}", self.memory.stack(self.register.SP));
-        // println!("[WRAM]\n{}", self.memory.wram());
-    }
-
-    fn fetch_program(&mut self) -> Program {
-        let pc = self.register.PC - 0x8000;
-        let program = Program::parse(&self.memory.prg_rom, pc);
-        println!(
-            "{:#x}, {}",
-            self.register.PC,
-            binary::DisplayBinary(
-                &self.memory.prg_rom[pc as usize..(pc as usize + program.orderset.length as usize)]
-            )
-        );
-        self.register.PC += program.orderset.length as u16;
+    /// PCの命令をバスから読み込んでデコードし、PCを次の命令へ進めます。
+    /// デコードした命令と、そのバイト列、バイト数を返します。
+    /// キャッシュにある命令は、バスから読み込まずにそれを使います。
+    fn fetch_program(&mut self) -> (Program, [u8; 3], usize) {
+        let pc = self.register.PC;
+        let bank = self.bus.bank(pc);
+        if let Some((program, bytes, length)) =
+            bank.and_then(|bank| self.decode_cache.get(pc, bank))
+        {
+            // 読み込みに副作用はないが、データバスには最後のバイトが残る
+            for _ in 0..length {
+                self.tick();
+            }
+            self.data_bus = bytes[length - 1];
+            self.register.PC = pc.wrapping_add(length as u16);
+            return (program, bytes, length);
+        }
+
+        let opcode = self.fetch(pc);
+        let length = OrderSets[opcode as usize].length;
+        let mut bytes = [opcode, 0x00, 0x00];
+        for (i, b) in bytes[..length].iter_mut().enumerate().skip(1) {
+            *b = self.fetch(pc.wrapping_add(i as u16));
+        }
+        self.register.PC = pc.wrapping_add(length as u16);
+        let program = Self::decode(pc, &bytes[..length]);
+        if let Some(bank) = bank {
+            // バンクをまたぐ命令はキャッシュしない
+            if (1..length).all(|i| self.bus.bank(pc.wrapping_add(i as u16)) == Some(bank)) {
+                self.decode_cache
+                    .insert(pc, bank, program, &bytes[..length]);
+            }
+        }
+        (program, bytes, length)
+    }
+
+    /// PCの命令をデコードします。PCは変更せず、バスの状態も変えません。
+    /// fetch_programと同じく、デコードした命令と、そのバイト列、バイト数を返します。
+    pub fn peek_program(&self) -> (Program, [u8; 3], usize) {
+        let pc = self.register.PC;
+        let length = OrderSets[self.bus.peek(pc) as usize].length;
+        let mut bytes = [0x00; 3];
+        for (i, b) in bytes[..length].iter_mut().enumerate() {
+            *b = self.bus.peek(pc.wrapping_add(i as u16));
+        }
+        (Self::decode(pc, &bytes[..length]), bytes, length)
+    }
+
+    /// pcに置かれていた命令のバイト列をデコードします。
+    fn decode(pc: u16, bytes: &[u8]) -> Program {
+        let mut program = Program::parse(bytes, 0);
+        program.addr = pc;
+        // 相対アドレスはバイト列の先頭からの位置なので、PCを基準にする
+        if let (Mode::Relative, Operand::Addr(offset)) = (program.orderset.mode, program.operand) {
+            program.operand = Operand::Addr(pc.wrapping_add(offset));
+        }
         program
     }
 
-    fn exec(&mut self, cmd: Command, operand: Operand, clock: usize) {
+    /// 命令を実行し、かかったクロックサイクル数を返します。
+    /// clockは命令の基本サイクル数で、分岐が成立した場合はそのペナルティを加算します。
+    fn exec(&mut self, cmd: Command, operand: Operand, clock: usize) -> usize {
         match cmd {
             Command::ADC => self.adc(operand),
             Command::SBC => self.sbc(operand),
             Command::AND => self.and(operand),
             Command::ORA => self.ora(operand),
             Command::EOR => self.eor(operand),
-            Command::ASL => self.asl(),
-            Command::LSR => self.lsr(),
-            Command::ROL => self.rol(),
-            Command::ROR => self.ror(),
-            Command::BCC => self.bcc(operand),
-            Command::BCS => self.bcs(operand),
-            Command::BEQ => self.beq(operand),
-            Command::BNE => self.bne(operand),
-            Command::BVC => self.bvc(operand),
-            Command::BVS => self.bvs(operand),
-            Command::BPL => self.bpl(operand),
-            Command::BMI => self.bmi(operand),
-            Command::BIT => self.bit(),
+            Command::ASL => self.asl(operand),
+            Command::LSR => self.lsr(operand),
+            Command::ROL => self.rol(operand),
+            Command::ROR => self.ror(operand),
+            Command::BCC => return clock + self.bcc(operand),
+            Command::BCS => return clock + self.bcs(operand),
+            Command::BEQ => return clock + self.beq(operand),
+            Command::BNE => return clock + self.bne(operand),
+            Command::BVC => return clock + self.bvc(operand),
+            Command::BVS => return clock + self.bvs(operand),
+            Command::BPL => return clock + self.bpl(operand),
+            Command::BMI => return clock + self.bmi(operand),
+            Command::BIT => self.bit(operand),
             Command::JMP => self.jmp(operand),
             Command::JSR => self.jsr(operand),
             Command::RTS => self.rts(),
@@ -117,162 +362,245 @@ impl Cpu {
             Command::PLA => self.pla(),
             Command::PHP => self.php(),
             Command::PLP => self.plp(),
-            Command::NOP => self.nop(),
+            Command::NOP => self.nop(operand),
+            Command::SLO => self.slo(operand),
+            Command::RLA => self.rla(operand),
+            Command::SRE => self.sre(operand),
+            Command::RRA => self.rra(operand),
+            Command::SAX => self.sax(operand),
+            Command::LAX => self.lax(operand),
+            Command::DCP => self.dcp(operand),
+            Command::ISC => self.isc(operand),
+            Command::ANC => self.anc(operand),
+            Command::ALR => self.alr(operand),
+            Command::ARR => self.arr(operand),
+            Command::AXS => self.axs(operand),
+            Command::XAA => self.xaa(operand),
+            Command::AHX => self.ahx(operand),
+            Command::TAS => self.tas(operand),
+            Command::SHY => self.shy(operand),
+            Command::SHX => self.shx(operand),
+            Command::LAS => self.las(operand),
+            Command::JAM => self.jam(),
         }
+        clock
+    }
+
+    /// インデックス付きのアドレス指定でページをまたいだときに+1サイクルかかる命令
+    /// 書き込みやRead-Modify-Writeの命令はページをまたぐかどうかに関係なく常に同じサイクル数です。
+    fn has_page_cross_penalty(cmd: Command) -> bool {
+        matches!(
+            cmd,
+            Command::ADC
+                | Command::SBC
+                | Command::AND
+                | Command::ORA
+                | Command::EOR
+                | Command::CMP
+                | Command::LDA
+                | Command::LDX
+                | Command::LDY
+                | Command::LAX
+                | Command::LAS
+                | Command::NOP
+        )
     }
 
-    fn update_operand_with_register(&mut self, program: &Program) -> Operand {
+    /// インデックスレジスタや間接参照を解決した実効アドレスと、
+    /// インデックスの加算でページ(上位バイト)をまたいだかどうかを返します。
+    fn update_operand_with_register(&mut self, program: &Program) -> (Operand, bool) {
         match program.orderset.mode {
-            Mode::Accumulator => Operand::None,
-            Mode::Implied => Operand::None,
-            Mode::Immediate => program.operand,
-            Mode::ZeroPage => program.operand,
+            Mode::Accumulator => (Operand::None, false),
+            Mode::Implied => (Operand::None, false),
+            Mode::Immediate => (program.operand, false),
+            Mode::ZeroPage => (program.operand, false),
             Mode::ZeroPageX => {
-                let (upper, lower) = program.operand.addrs();
-                let lower = lower.wrapping_add(self.register.X as u8);
-                Operand::Addr(binary::u8u8_to_u16(upper, lower))
+                let base = binary::lower_only(self.addr(program.operand));
+                (
+                    Operand::Addr(zero_page_indexed(base, self.register.X)),
+                    false,
+                )
             }
             Mode::ZeroPageY => {
-                let (upper, lower) = program.operand.addrs();
-                let lower = lower.wrapping_add(self.register.Y as u8);
-                Operand::Addr(binary::u8u8_to_u16(upper, lower))
+                let base = binary::lower_only(self.addr(program.operand));
+                (
+                    Operand::Addr(zero_page_indexed(base, self.register.Y)),
+                    false,
+                )
             }
-            Mode::Absolute => program.operand,
+            Mode::Absolute => (program.operand, false),
             Mode::AbsoluteX => {
-                let addr = program.operand.addr();
-                Operand::Addr(addr.wrapping_add(self.register.X as u16))
+                let base = self.addr(program.operand);
+                Self::indexed(base, self.register.X)
             }
             Mode::AbsoluteY => {
-                let addr = program.operand.addr();
-                Operand::Addr(addr.wrapping_add(self.register.Y as u16))
+                let base = self.addr(program.operand);
+                Self::indexed(base, self.register.Y)
             }
-            Mode::Relative => program.operand,
+            Mode::Relative => (program.operand, false),
             Mode::Indirect => {
-                let addr = program.operand.addr();
-                let lower = self.memory.read(addr);
-                let upper = self.memory.read(addr + 1);
-                Operand::Addr(binary::u8u8_to_u16(upper, lower))
+                let addr = self.addr(program.operand);
+                (Operand::Addr(self.read_pointer(addr)), false)
             }
             Mode::IndirectX => {
-                let (upper, lower) = program.operand.addrs();
-                let addr = binary::u8u8_to_u16(upper, lower.wrapping_add(self.register.X));
-                let lower = self.memory.read(addr);
-                let upper = self.memory.read(addr + 1);
-                Operand::Addr(binary::u8u8_to_u16(upper, lower))
+                let base = binary::lower_only(self.addr(program.operand));
+                let pointer = zero_page_indexed(base, self.register.X);
+                (Operand::Addr(self.read_pointer(pointer)), false)
             }
             Mode::IndirectY => {
-                let addr = program.operand.addr();
-                let upper = self.memory.read(addr);
-                let lower = self.memory.read(addr + 1);
-                let addr = binary::u8u8_to_u16(upper, lower).wrapping_add(self.register.Y as u16);
-                Operand::Addr(addr)
+                let pointer = self.addr(program.operand);
+                let base = self.read_pointer(pointer);
+                Self::indexed(base, self.register.Y)
             }
-            Mode::NONE => program.operand,
+            Mode::NONE => (program.operand, false),
         }
     }
 
+    /// addrを下位バイト、その次のアドレスを上位バイトとして読み込みます。
+    /// 次のアドレスはページをまたがず、$xxFFの次は$xx00になります。
+    fn read_pointer(&mut self, addr: u16) -> u16 {
+        let lower = self.read(addr);
+        let upper = self.read(next_in_page(addr));
+        binary::u8u8_to_u16(upper, lower)
+    }
+
+    /// base + index の実効アドレスと、ページをまたいだかどうか
+    fn indexed(base: u16, index: u8) -> (Operand, bool) {
+        let addr = base.wrapping_add(index as u16);
+        (
+            Operand::Addr(addr),
+            binary::upper_only(base) != binary::upper_only(addr),
+        )
+    }
+
     /// ADC (Add M to A with C)	A + M + C -> A
     /// flags: N V Z C
     fn adc(&mut self, operand: Operand) {
         let m = self.to_data(operand);
-        dbg!(m as u16 + self.register.A as u16 + self.register.P.c() as u16);
-        unimplemented!();
+        self.add_with_carry(m);
     }
 
     /// SBC (Subtract M from A with C)	A - M - not C -> A
     /// flags: N V Z C
+    /// A - M - (1 - C) は A + !M + C と同じなので、ADCと同じ加算で求める。
     fn sbc(&mut self, operand: Operand) {
         let m = self.to_data(operand);
-        dbg!(m as u16 - self.register.A as u16 - !self.register.P.c() as u16);
-        unimplemented!();
+        self.add_with_carry(!m);
+    }
+
+    /// A + M + C -> A
+    /// Vフラグは同じ符号同士の加算で結果の符号が変わったときにセットする。
+    fn add_with_carry(&mut self, m: u8) {
+        let a = self.register.A;
+        let sum = a as u16 + m as u16 + self.register.P.c() as u16;
+        let v = sum as u8;
+        let overflow = (a ^ v) & (m ^ v) & 0b1000_0000 == 0b1000_0000;
+        self.register.A = v;
+        self.register.P = self
+            .register
+            .P
+            .set_n(flag_n(v))
+            .set_v(overflow)
+            .set_z(flag_z(v))
+            .set_c(sum > 0x00ff);
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BCC (Branch on C clear) Cフラグがクリアされていれば分岐します。
     /// flags: none
-    fn bcc(&mut self, operand: Operand) {
-        if self.register.P.c() {
-            self.register.PC = operand.addr();
-        }
+    fn bcc(&mut self, operand: Operand) -> usize {
+        self.branch(!self.register.P.c(), operand)
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BCS (Branch on C set) Cフラグがセットされていれば分岐します。
     /// flags: none
-    fn bcs(&mut self, operand: Operand) {
-        if !self.register.P.c() {
-            self.register.PC = operand.addr();
-        }
+    fn bcs(&mut self, operand: Operand) -> usize {
+        self.branch(self.register.P.c(), operand)
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BEQ (Branch on Z set (result equal)) Zフラグがセットされていれば分岐します。
     /// flags: none
-    fn beq(&mut self, operand: Operand) {
-        if self.register.P.z() {
-            self.register.PC = operand.addr();
-        }
+    fn beq(&mut self, operand: Operand) -> usize {
+        self.branch(self.register.P.z(), operand)
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BNE (Branch on Z clear (result not equal)) Zフラグがクリアされていれば分岐します。
     /// flags: none
-    fn bne(&mut self, operand: Operand) {
-        if !self.register.P.z() {
-            self.register.PC = operand.addr();
-        }
+    fn bne(&mut self, operand: Operand) -> usize {
+        self.branch(!self.register.P.z(), operand)
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BVC (Branch on V clear) Vフラグがクリアされていれば分岐します。
     /// flags: none
-    fn bvc(&mut self, operand: Operand) {
-        if self.register.P.v() {
-            self.register.PC = operand.addr();
-        }
+    fn bvc(&mut self, operand: Operand) -> usize {
+        self.branch(!self.register.P.v(), operand)
     }
 
     /// BVS (Branch on V set) Vフラグがセットされていれば分岐します。
     /// flags: none
-    fn bvs(&mut self, operand: Operand) {
-        if !self.register.P.v() {
-            self.register.PC = operand.addr();
-        }
+    fn bvs(&mut self, operand: Operand) -> usize {
+        self.branch(self.register.P.v(), operand)
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BPL (Branch on N clear (result plus)) Nフラグがクリアされていれば分岐します。
     /// flags: none
-    fn bpl(&mut self, operand: Operand) {
-        if self.register.P.n() {
-            self.register.PC = operand.addr();
-        }
+    fn bpl(&mut self, operand: Operand) -> usize {
+        self.branch(!self.register.P.n(), operand)
     }
 
     /// 条件が成立した場合、リラティブ・アドレス指定により 分岐先のアドレスをPCへストアします。
     /// 成立しなかった場合、分岐命令の先頭アドレス+2をPCへストアします（単に次の命令アドレス）。
     /// BMI (Branch on N set (result minus)) Nフラグがセットされていれば分岐します。
     /// flags: none
-    fn bmi(&mut self, operand: Operand) {
-        if !self.register.P.n() {
-            self.register.PC = operand.addr();
+    fn bmi(&mut self, operand: Operand) -> usize {
+        self.branch(self.register.P.n(), operand)
+    }
+
+    /// 分岐条件が成立していれば、分岐先のアドレスをPCへストアします。
+    /// 追加でかかったクロックサイクル数を返します。
+    /// 分岐が成立すると+1、分岐先が次の命令と異なるページであればさらに+1です。
+    fn branch(&mut self, condition: bool, operand: Operand) -> usize {
+        if !condition {
+            return 0;
+        }
+        let next = self.register.PC;
+        let addr = self.addr(operand);
+        self.register.PC = addr;
+        if binary::upper_only(next) != binary::upper_only(addr) {
+            2
+        } else {
+            1
         }
     }
 
-    fn bit(&mut self) {
-        unimplemented!();
+    /// BIT (Test Bits in M with A)	A and M, M7 -> N, M6 -> V
+    /// flags: N V Z
+    /// Zフラグは A and M の結果で、N、Vフラグはメモリ値のビット7、ビット6をそのままストアします。
+    fn bit(&mut self, operand: Operand) {
+        let m = self.to_data(operand);
+        self.register.P = self
+            .register
+            .P
+            .set_n(flag_n(m))
+            .set_v(m & 0b0100_0000 == 0b0100_0000)
+            .set_z(flag_z(self.register.A & m));
     }
     ///JMP (Jump to new location)
     /// ADDR -> PC
     /// flags: none
     fn jmp(&mut self, operand: Operand) {
-        self.register.PC = operand.addr();
+        self.register.PC = self.addr(operand);
     }
     ///JSR (Jump to new location saving return address)
     /// ADDR -> PC
@@ -288,10 +616,10 @@ impl Cpu {
     /// リターンサブルーチン命令（RTS）によってインクリメントします。
     fn jsr(&mut self, operand: Operand) {
         // トラップ回避のために-1
-        let pc = self.register.PC - 1;
+        let pc = self.register.PC.wrapping_sub(1);
         self.push_stack(binary::upper_only(pc));
         self.push_stack(binary::lower_only(pc));
-        self.register.PC = operand.addr();
+        self.register.PC = self.addr(operand);
     }
 
     /// RTS (Return from Subroutine)
@@ -302,8 +630,7 @@ impl Cpu {
     fn rts(&mut self) {
         let lower = self.pop_stack();
         let upper = self.pop_stack();
-        self.register.PC = binary::u8u8_to_u16(upper, lower);
-        self.register.PC += 1;
+        self.register.PC = binary::u8u8_to_u16(upper, lower).wrapping_add(1);
     }
     ///割り込みが確認された時、Iフラグがセットされていれば割り込みは無視します。
     ///Iフラグがクリアされていれば、割り込み動作を開始します。
@@ -312,15 +639,22 @@ impl Cpu {
     ///次にIフラグをセットし、最後にPCの下位バイトを$FFFEから、上位バイトを$FFFFからフェッチします。
     /// IRQと異なる点はBフラグとPCの扱いのみで、あとは同じです。
     /// BRKではPCに1を加算するため、BRK命令のあるアドレス+2番地がリターンアドレスとなります。
+    /// BRK自体はIフラグに関係なく実行されます。
     fn brk(&mut self) {
-        if self.register.P.i() {
-            // 何もしない
-        } else {
-            // unimplemented!();
-        }
+        self.register.PC = self.register.PC.wrapping_add(1);
+        self.interrupt(Interrupt::BRK);
     }
+    /// RTI (Return from Interrupt)
+    /// 割り込みから復帰します。
+    /// ステータスレジスタ、PCの下位バイト、上位バイトの順にスタックからポップします。
+    /// RTSと異なり、ポップしたPCはインクリメントしません。
+    /// flags: all
     fn rti(&mut self) {
-        unimplemented!();
+        let p = self.pop_stack();
+        self.register.P = StatusRegister::from_stack(p);
+        let lower = self.pop_stack();
+        let upper = self.pop_stack();
+        self.register.PC = binary::u8u8_to_u16(upper, lower);
     }
 
     /// AND ("AND" M with A)	A and M -> A
@@ -328,58 +662,84 @@ impl Cpu {
     fn and(&mut self, operand: Operand) {
         let v = self.register.A & self.to_data(operand);
         self.register.A = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// ORA ("OR" M with A)	A or M -> A
     ///  flags: N Z
     fn ora(&mut self, operand: Operand) {
         let v = self.register.A | self.to_data(operand);
         self.register.A = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// EOR ("Exclusive-OR" M with A)	A eor M -> A
     ///  flags: N Z
     fn eor(&mut self, operand: Operand) {
         let v = self.register.A ^ self.to_data(operand);
         self.register.A = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
 
     /// ASL (Arithmetic shift left one bit)
-    /// Aを左シフト、ビット0には0
-    /// C <- Aのビット7
-    fn asl(&mut self) {
-        let a = self.register.A;
-        self.register.P = self.register.P.set_c((a & 0b1000_0000) == 0b1000_0000);
-        self.register.A = a << 1;
+    /// A(M)を左シフト、ビット0には0
+    /// C <- A(M)のビット7
+    /// flags: N Z C
+    fn asl(&mut self, operand: Operand) {
+        let (m, v) = self.read_modify_write(operand, |m| m << 1);
+        self.register.P = self
+            .register
+            .P
+            .set_n(flag_n(v))
+            .set_z(flag_z(v))
+            .set_c((m & 0b1000_0000) == 0b1000_0000);
     }
     /// LSR (Logical shift right one bit)
-    /// Aを右シフト、ビット7にはpc
-    /// Aのビット0 -> C
-    fn lsr(&mut self) {
-        let a = self.register.A;
-        self.register.P = self.register.P.set_c((a & 0b0000_0001) == 0b0000_0001);
-        self.register.A = a >> 1;
+    /// A(M)を右シフト、ビット7には0
+    /// A(M)のビット0 -> C
+    /// flags: N Z C
+    fn lsr(&mut self, operand: Operand) {
+        let (m, v) = self.read_modify_write(operand, |m| m >> 1);
+        self.register.P = self
+            .register
+            .P
+            .set_n(flag_n(v))
+            .set_z(flag_z(v))
+            .set_c((m & 0b0000_0001) == 0b0000_0001);
     }
     /// ROL (Rotate left one bit)
-    /// Aを左シフト、ビット0にはC
-    /// C <- Aのビット7
-    fn rol(&mut self) {
-        let a = self.register.A;
-        let c = self.register.P.c();
-        self.register.P = self.register.P.set_c((a & 0b1000_0000) == 0b1000_0000);
-        self.register.A = (a << 1) & (if c { 0b0000_0001 } else { 0b0000_0000 });
-        unimplemented!("なんかおかしいっぽい");
+    /// A(M)を左シフト、ビット0にはC
+    /// C <- A(M)のビット7
+    /// flags: N Z C
+    fn rol(&mut self, operand: Operand) {
+        let c = if self.register.P.c() {
+            0b0000_0001
+        } else {
+            0b0000_0000
+        };
+        let (m, v) = self.read_modify_write(operand, |m| (m << 1) | c);
+        self.register.P = self
+            .register
+            .P
+            .set_n(flag_n(v))
+            .set_z(flag_z(v))
+            .set_c((m & 0b1000_0000) == 0b1000_0000);
     }
     /// ROR (Rotate right one bit)
-    /// Aを右シフト、ビット7にはC
-    /// Aのビット0 -> C
-    fn ror(&mut self) {
-        let a = self.register.A;
-        let c = self.register.P.c();
-        self.register.P = self.register.P.set_c((a & 0b0000_0001) == 0b0000_0001);
-        self.register.A = (a >> 1) & (if c { 0b1000_0000 } else { 0b0000_0000 });
-        unimplemented!("なんかおかしいっぽい");
+    /// A(M)を右シフト、ビット7にはC
+    /// A(M)のビット0 -> C
+    /// flags: N Z C
+    fn ror(&mut self, operand: Operand) {
+        let c = if self.register.P.c() {
+            0b1000_0000
+        } else {
+            0b0000_0000
+        };
+        let (m, v) = self.read_modify_write(operand, |m| (m >> 1) | c);
+        self.register.P = self
+            .register
+            .P
+            .set_n(flag_n(v))
+            .set_z(flag_z(v))
+            .set_c((m & 0b0000_0001) == 0b0000_0001);
     }
 
     /// 演算の結果によって、フラグをセットします。
@@ -387,84 +747,75 @@ impl Cpu {
     /// CMP (Compare M and A)	A - M
     /// flags: N Z C
     fn cmp(&mut self, operand: Operand) {
-        let v = self.register.A - self.to_data(operand);
-        self.register.P = self
-            .register
-            .P
-            .set_n(flag_n(v))
-            .set_n(flag_z(v))
-            .set_c(v >= 1);
-        unimplemented!("calicurate");
+        let m = self.to_data(operand);
+        self.compare(self.register.A, m);
     }
     /// 演算の結果によって、フラグをセットします。
     /// Cフラグは、正かゼロのときセットし、負のときクリアします。 （単にキャリーをストア）
     /// CPX (Compare M and X)	X - M
     /// flags: N Z C
     fn cpx(&mut self, operand: Operand) {
-        let v = self.register.X - self.to_data(operand);
-        self.register.P = self
-            .register
-            .P
-            .set_n(flag_n(v))
-            .set_n(flag_z(v))
-            .set_c(v >= 1);
-        unimplemented!("calicurate");
+        let m = self.to_data(operand);
+        self.compare(self.register.X, m);
     }
     /// 演算の結果によって、フラグをセットします。
     /// Cフラグは、正かゼロのときセットし、負のときクリアします。 （単にキャリーをストア）
     /// CPY (Compare M and Y)	Y - M
     /// flags: N Z C
     fn cpy(&mut self, operand: Operand) {
-        let v = self.register.Y - self.to_data(operand);
+        let m = self.to_data(operand);
+        self.compare(self.register.Y, m);
+    }
+    /// r - M の結果でN、Zフラグを、r >= M のときCフラグをセットします。
+    fn compare(&mut self, r: u8, m: u8) {
+        let v = r.wrapping_sub(m);
         self.register.P = self
             .register
             .P
             .set_n(flag_n(v))
-            .set_n(flag_z(v))
-            .set_c(v >= 1);
-        unimplemented!("calicurate");
+            .set_z(flag_z(v))
+            .set_c(r >= m);
     }
+
     /// INC (Increment M by one)	M + 1 -> M
     /// flags: N Z
     fn inc(&mut self, operand: Operand) {
-        let v = self.to_data(operand) + 1;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        let (_, v) = self.read_modify_write(operand, |m| m.wrapping_add(1));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// INX (Increment X by one)	X + 1 -> X
     /// flags: N Z
     fn inx(&mut self) {
-        let v = self.register.X + 1;
+        let v = self.register.X.wrapping_add(1);
         self.register.X = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// INY (Increment Y by one)	Y + 1 -> Y
     /// flags: N Z
     fn iny(&mut self) {
-        let v = self.register.Y + 1;
+        let v = self.register.Y.wrapping_add(1);
         self.register.Y = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// DEC (Decrement M by one)	M - 1 -> M
     /// flags: N Z
     fn dec(&mut self, operand: Operand) {
-        let addr = operand.addr();
-        let v = self.to_data(operand) - 1;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
-        self.memory.write(addr, v);
+        let (_, v) = self.read_modify_write(operand, |m| m.wrapping_sub(1));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// DEX (Decrement X by one)	X - 1 -> X
     /// flags: N Z
     fn dex(&mut self) {
-        let v = self.register.X - 1;
+        let v = self.register.X.wrapping_sub(1);
         self.register.X = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
     /// DEY (Decrement Y by one)	Y - 1 -> Y
     /// flags: N Z
     fn dey(&mut self) {
-        let v = self.register.Y - 1;
+        let v = self.register.Y.wrapping_sub(1);
         self.register.Y = v;
-        self.register.P = self.register.P.set_n(flag_n(v)).set_n(flag_z(v));
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
 
     /// CLC (Clear C flag)	0 -> C
@@ -506,20 +857,20 @@ impl Cpu {
     /// STA (Store A to M)	A -> M
     /// flags: none
     fn sta(&mut self, operand: Operand) {
-        let addr = operand.addr();
-        self.memory.write(addr, self.register.A);
+        let addr = self.addr(operand);
+        self.write(addr, self.register.A);
     }
     /// STX (Store X to M)	X -> M
     /// flags: none
     fn stx(&mut self, operand: Operand) {
-        let addr = operand.addr();
-        self.memory.write(addr, self.register.X);
+        let addr = self.addr(operand);
+        self.write(addr, self.register.X);
     }
     /// STY (Store Y to M)	Y -> M
     /// flags: none
     fn sty(&mut self, operand: Operand) {
-        let addr = operand.addr();
-        self.memory.write(addr, self.register.Y);
+        let addr = self.addr(operand);
+        self.write(addr, self.register.Y);
     }
 
     /// LDA (Load A from M)	M -> A
@@ -580,7 +931,6 @@ impl Cpu {
     /// flags: N Z
     fn tsx(&mut self) {
         let v = self.register.SP;
-        let v = (v >> 8) as u8;
         self.register.X = v;
         self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
     }
@@ -588,7 +938,7 @@ impl Cpu {
     /// TXS (Transfer X to S)	X -> S
     /// flags: none
     fn txs(&mut self) {
-        self.register.SP = binary::u8u8_to_u16(0x01, self.register.X);
+        self.register.SP = self.register.X;
     }
 
     /// PHA (Push A on stack)	A -> stack
@@ -607,8 +957,9 @@ impl Cpu {
 
     /// PHP (Push P on stack)	P -> stack
     /// flags: none
+    /// BRKと同じく、Bフラグをセットした値をプッシュします。
     fn php(&mut self) {
-        self.push_stack(u8::from(self.register.P));
+        self.push_stack(self.register.P.to_stack(true));
     }
 
     /// PLP (Pull P from stack)	stack -> P
@@ -618,64 +969,1883 @@ impl Cpu {
     /// plpでフラグをもとに戻すってこと？
     fn plp(&mut self) {
         let v = self.pop_stack();
-        self.register.P = StatusRegister::from(v);
+        self.register.P = StatusRegister::from_stack(v);
+    }
+
+    /// NOP (No operation)
+    /// 非公式のNOPはオペランドのアドレスを読み込むだけで、何もしません。
+    fn nop(&mut self, operand: Operand) {
+        if let Operand::Addr(addr) = operand {
+            self.read(addr);
+        }
+    }
+
+    /// SLO (ASL + ORA)	M << 1 -> M, A or M -> A
+    /// flags: N Z C
+    fn slo(&mut self, operand: Operand) {
+        let (m, v) = self.read_modify_write(operand, |m| m << 1);
+        self.register.P = self.register.P.set_c((m & 0b1000_0000) == 0b1000_0000);
+        self.ora(Operand::Data(v));
+    }
+
+    /// RLA (ROL + AND)	M rol 1 -> M, A and M -> A
+    /// flags: N Z C
+    fn rla(&mut self, operand: Operand) {
+        let c = if self.register.P.c() {
+            0b0000_0001
+        } else {
+            0b0000_0000
+        };
+        let (m, v) = self.read_modify_write(operand, |m| (m << 1) | c);
+        self.register.P = self.register.P.set_c((m & 0b1000_0000) == 0b1000_0000);
+        self.and(Operand::Data(v));
+    }
+
+    /// SRE (LSR + EOR)	M >> 1 -> M, A eor M -> A
+    /// flags: N Z C
+    fn sre(&mut self, operand: Operand) {
+        let (m, v) = self.read_modify_write(operand, |m| m >> 1);
+        self.register.P = self.register.P.set_c((m & 0b0000_0001) == 0b0000_0001);
+        self.eor(Operand::Data(v));
+    }
+
+    /// RRA (ROR + ADC)	M ror 1 -> M, A + M + C -> A
+    /// ADCはRORで押し出されたCを使う
+    /// flags: N V Z C
+    fn rra(&mut self, operand: Operand) {
+        let c = if self.register.P.c() {
+            0b1000_0000
+        } else {
+            0b0000_0000
+        };
+        let (m, v) = self.read_modify_write(operand, |m| (m >> 1) | c);
+        self.register.P = self.register.P.set_c((m & 0b0000_0001) == 0b0000_0001);
+        self.add_with_carry(v);
+    }
+
+    /// SAX (Store A and X)	A and X -> M
+    /// flags: none
+    fn sax(&mut self, operand: Operand) {
+        let addr = self.addr(operand);
+        self.write(addr, self.register.A & self.register.X);
+    }
+
+    /// LAX (LDA + LDX)	M -> A, X
+    /// LAX #iは不安定な命令だが、(A or $FF) and #i として扱う。
+    /// flags: N Z
+    fn lax(&mut self, operand: Operand) {
+        let v = self.to_data(operand);
+        self.register.A = v;
+        self.register.X = v;
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
+    }
+
+    /// DCP (DEC + CMP)	M - 1 -> M, A - M
+    /// flags: N Z C
+    fn dcp(&mut self, operand: Operand) {
+        let (_, v) = self.read_modify_write(operand, |m| m.wrapping_sub(1));
+        self.compare(self.register.A, v);
+    }
+
+    /// ISC (INC + SBC)	M + 1 -> M, A - M - not C -> A
+    /// flags: N V Z C
+    fn isc(&mut self, operand: Operand) {
+        let (_, v) = self.read_modify_write(operand, |m| m.wrapping_add(1));
+        self.add_with_carry(!v);
+    }
+
+    /// ANC (AND + N -> C)	A and M -> A
+    /// flags: N Z C
+    fn anc(&mut self, operand: Operand) {
+        self.and(operand);
+        self.register.P = self.register.P.set_c(self.register.P.n());
+    }
+
+    /// ALR (AND + LSR A)	(A and M) >> 1 -> A
+    /// flags: N Z C
+    fn alr(&mut self, operand: Operand) {
+        self.and(operand);
+        self.lsr(Operand::None);
+    }
+
+    /// ARR (AND + ROR A)	(A and M) ror 1 -> A
+    /// Cは結果のビット6、Vは結果のビット6とビット5の排他的論理和
+    /// flags: N V Z C
+    fn arr(&mut self, operand: Operand) {
+        self.and(operand);
+        self.ror(Operand::None);
+        let v = self.register.A;
+        let bit6 = (v & 0b0100_0000) == 0b0100_0000;
+        let bit5 = (v & 0b0010_0000) == 0b0010_0000;
+        self.register.P = self.register.P.set_c(bit6).set_v(bit6 ^ bit5);
+    }
+
+    /// AXS (A and X minus M)	(A and X) - M -> X
+    /// CMPと同じようにCをセットし、借りは無視する
+    /// flags: N Z C
+    fn axs(&mut self, operand: Operand) {
+        let m = self.to_data(operand);
+        let ax = self.register.A & self.register.X;
+        self.register.X = ax.wrapping_sub(m);
+        self.compare(ax, m);
+    }
+
+    /// XAA	(A or magic) and X and M -> A
+    /// magicは個体差があるため、LAX #iと同じく$FFとして扱う。
+    /// flags: N Z
+    fn xaa(&mut self, operand: Operand) {
+        let v = self.register.X & self.to_data(operand);
+        self.register.A = v;
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
+    }
+
+    /// AHX	A and X and (H + 1) -> M
+    /// flags: none
+    fn ahx(&mut self, operand: Operand) {
+        let v = self.register.A & self.register.X;
+        self.store_and_high(operand, self.register.Y, v);
+    }
+
+    /// TAS	A and X -> S, A and X and (H + 1) -> M
+    /// flags: none
+    fn tas(&mut self, operand: Operand) {
+        let v = self.register.A & self.register.X;
+        self.register.SP = v;
+        self.store_and_high(operand, self.register.Y, v);
+    }
+
+    /// SHY	Y and (H + 1) -> M
+    /// flags: none
+    fn shy(&mut self, operand: Operand) {
+        self.store_and_high(operand, self.register.X, self.register.Y);
+    }
+
+    /// SHX	X and (H + 1) -> M
+    /// flags: none
+    fn shx(&mut self, operand: Operand) {
+        self.store_and_high(operand, self.register.Y, self.register.X);
+    }
+
+    /// LAS	M and S -> A, X, S
+    /// flags: N Z
+    fn las(&mut self, operand: Operand) {
+        let v = self.to_data(operand) & self.register.SP;
+        self.register.A = v;
+        self.register.X = v;
+        self.register.SP = v;
+        self.register.P = self.register.P.set_n(flag_n(v)).set_z(flag_z(v));
+    }
+
+    /// JAM (KIL)
+    /// CPUを停止させます。PCはJAM命令に留まり、RESETまで何も実行しません。
+    fn jam(&mut self) {
+        self.register.PC = self.register.PC.wrapping_sub(1);
+        if !self.jammed {
+            self.jammed = true;
+            self.fault(EmuError::Jammed {
+                addr: self.register.PC,
+            });
+        }
+    }
+
+    /// SHX、SHY、AHX、TASの書き込み
+    /// 値にはインデックスを加算する前のアドレスの上位バイト+1をANDする。
+    /// ページをまたいだ場合は、書き込むアドレスの上位バイトもその値になる。
+    fn store_and_high(&mut self, operand: Operand, index: u8, v: u8) {
+        let addr = self.addr(operand);
+        let base = addr.wrapping_sub(index as u16);
+        let v = v & binary::upper_only(base).wrapping_add(1);
+        let addr = if binary::upper_only(base) != binary::upper_only(addr) {
+            binary::u8u8_to_u16(v, binary::lower_only(addr))
+        } else {
+            addr
+        };
+        self.write(addr, v);
+    }
+
+    /// 次の命令の前に処理すべき割り込みを返します。
+    /// NMIはIRQより優先し、IRQはIフラグがセットされていれば無視します。
+    fn poll_interrupt(&mut self) -> Option<Interrupt> {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            Some(Interrupt::NMI)
+        } else if self.irq_line && !self.register.P.i() {
+            Some(Interrupt::IRQ)
+        } else {
+            None
+        }
+    }
+
+    /// NMI、IRQ、BRKの割り込み動作
+    /// PCの上位バイト、下位バイト、ステータスレジスタを順にスタックへ格納し、
+    /// Iフラグをセットしたのち、割り込みベクタからPCをフェッチします。
+    /// スタックへ格納するステータスレジスタのBフラグはBRKのときだけセットします。
+    fn interrupt(&mut self, interrupt: Interrupt) {
+        let pc = self.register.PC;
+        self.push_stack(binary::upper_only(pc));
+        self.push_stack(binary::lower_only(pc));
+        self.push_stack(self.register.P.to_stack(interrupt == Interrupt::BRK));
+        self.register.P = self.register.P.set_i(true);
+        self.register.PC = self.read_vector(interrupt);
     }
 
-    fn nop(&mut self) {
-        // Do Nothing
+    /// 割り込みベクタからアドレスを読み込みます。
+    fn read_vector(&mut self, interrupt: Interrupt) -> u16 {
+        let vector = interrupt.vector();
+        let lower = self.read(vector);
+        let upper = self.read(vector + 1);
+        binary::u8u8_to_u16(upper, lower)
     }
 
+    /// スタックは$0100～$01FFで、SPは$00の次は$FFに戻る
     fn push_stack(&mut self, v: u8) {
-        self.memory.write(self.register.SP, v);
-        self.register.SP -= 1;
+        self.write(stack_addr(self.register.SP), v);
+        self.register.SP = self.register.SP.wrapping_sub(1);
     }
 
+    /// SPは次に書き込む位置を指しているので、インクリメントしてから読み込む
     fn pop_stack(&mut self) -> u8 {
-        let v = self.memory.read(self.register.SP);
-        self.register.SP += 1;
-        v
+        self.register.SP = self.register.SP.wrapping_add(1);
+        self.read(stack_addr(self.register.SP))
     }
 
-    /// dataならData、アドレスならそのアドレスのデータを読み込む
-    fn to_data(&self, operand: Operand) -> u8 {
+    /// Read-Modify-Write命令の共通処理
+    /// operandがNoneならアキュムレータを、アドレスならそのアドレスのデータを変更する。
+    /// 実機ではメモリを変更する場合、変更前の値を一度書き戻してから変更後の値を書き込むので、
+    /// 書き込みに副作用のあるレジスタ(マッパーなど)のためにそれを再現する。
+    /// 戻り値は(変更前, 変更後)
+    fn read_modify_write<F>(&mut self, operand: Operand, f: F) -> (u8, u8)
+    where
+        F: FnOnce(u8) -> u8,
+    {
         match operand {
-            Operand::Data(v) => v,
-            Operand::Addr(addr) => self.memory.read(addr),
-            _ => unimplemented!(),
+            Operand::None => {
+                let m = self.register.A;
+                let v = f(m);
+                self.register.A = v;
+                (m, v)
+            }
+            Operand::Addr(addr) => {
+                let m = self.read(addr);
+                self.write(addr, m);
+                let v = f(m);
+                self.write(addr, v);
+                (m, v)
+            }
+            Operand::Data(_) => {
+                // 変更する場所がないので何も書き込まず、データバスに残っている値を返す
+                self.fault(EmuError::UnexpectedOperand(operand));
+                (self.data_bus, self.data_bus)
+            }
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ines::sprite::Sprite;
-    use crate::io;
-    use crate::ppu::io_register::IORegister;
-    use crate::ppu::PPU;
-    use std::{thread, time};
-
-    #[test]
-    fn it_cpu_run<'a>() {
-        let mut contents =
-            io::read_to_binary("../docs/demo/sample1.nes").expect("Don't warry, it is debug");
+    /// 命令をバスから読み込む
+    /// どの機器もデータバスを駆動しなかった場合や読み込みに失敗した場合は、
+    /// 最後にデータバスに乗った値(多くは直前に読み込んだオペランドの上位バイト)を返します。
+    fn fetch(&mut self, addr: u16) -> u8 {
+        self.tick();
+        let v = match self.bus.read(addr) {
+            Ok(v) => v,
+            Err(EmuError::OpenBus { .. }) => self.data_bus,
+            Err(e) => {
+                self.fault(e);
+                self.data_bus
+            }
+        };
+        self.data_bus = v;
+        v
+    }
 
-        let ines = crate::ines::parser(&mut contents).unwrap();
-        println!("{:?}", ines);
-        let sprites: Vec<Sprite> = Sprite::parse_sprites(&ines.character_rom_data);
-        let mut cpu = Cpu::new(ines);
-        let mut ppu = PPU::new(&sprites[..]);
+    /// メモリから読み込み、実行中の命令の記録に残す
+    fn read(&mut self, addr: u16) -> u8 {
+        let v = self.fetch(addr);
+        self.reads.push((addr, v));
+        v
+    }
 
-        fn game_loop(cpu: &mut Cpu, ppu: &mut PPU) {
-            cpu.run();
-            ppu.refresh(&mut cpu.memory.ppu);
-            ppu.draw();
+    /// メモリへ書き込み、実行中の命令の記録に残す
+    /// 書き込みに失敗した場合は、実機と同じく書き込みを捨てます。
+    fn write(&mut self, addr: u16, v: u8) {
+        self.tick();
+        if let Err(e) = self.bus.write(addr, v) {
+            self.fault(e);
         }
+        self.decode_cache.invalidate(addr);
+        self.data_bus = v;
+        if addr == OAMDMA {
+            self.dma = Some(v);
+        }
+        self.writes.push((addr, v));
+    }
 
-        loop {
-            let mut buffer = String::new();
-            std::io::stdin().read_line(&mut buffer).unwrap();
-            game_loop(&mut cpu, &mut ppu);
+    /// OAM DMA
+    /// $XX00から256バイトを読み込んでOAMDATAへ書き込み、かかったクロックサイクル数を返します。
+    /// 書き込みの完了を待つ1サイクルと256回の読み書きで513サイクル、
+    /// 奇数サイクルから始まる場合は読み込みのタイミングに揃えるため、さらに1サイクルかかります。
+    /// 転送は命令の記録には残しません。
+    fn oam_dma(&mut self, page: u8, cycles: u64) -> usize {
+        for i in 0..=0xff {
+            let v = self.fetch(binary::u8u8_to_u16(page, i));
+            self.tick();
+            if let Err(e) = self.bus.write(OAMDATA_INDEX, v) {
+                self.fault(e);
+            }
+        }
+        if cycles % 2 == 1 {
+            514
+        } else {
+            513
         }
     }
+
+    /// バスを1サイクル進めます。
+    /// NMIはサイクルごとに、そのサイクルの読み書きより前に検出し、次の命令の前に発生させる。
+    fn tick(&mut self) {
+        self.bus.tick();
+        self.bus_cycles += 1;
+        let nmi = self.bus.nmi();
+        self.set_nmi(nmi);
+    }
+
+    /// 読み書きのなかったサイクルの分だけバスを進め、cyclesに追いつかせます。
+    fn catch_up(&mut self) {
+        while self.bus_cycles < self.cycles {
+            self.tick();
+        }
+    }
+
+    /// dataならData、アドレスならそのアドレスのデータを読み込む
+    fn to_data(&mut self, operand: Operand) -> u8 {
+        match operand {
+            Operand::Data(v) => v,
+            Operand::Addr(addr) => self.read(addr),
+            Operand::None => {
+                self.fault(EmuError::UnexpectedOperand(operand));
+                0x00
+            }
+        }
+    }
+
+    /// オペランドのアドレス
+    fn addr(&mut self, operand: Operand) -> u16 {
+        operand.addr().unwrap_or_else(|e| {
+            self.fault(e);
+            0x0000
+        })
+    }
+
+    /// FaultPolicyに従ってEmuErrorを処理します。
+    fn fault(&mut self, error: EmuError) {
+        let fault = Fault {
+            error,
+            pc: self.current,
+        };
+        match self.fault_policy {
+            FaultPolicy::Ignore => {}
+            FaultPolicy::Log => eprintln!("[Fault]{}", fault),
+            FaultPolicy::Break => {
+                if self.fault.is_none() {
+                    self.fault = Some(fault);
+                }
+            }
+        }
+    }
+}
+
+/// SPが指すスタックのアドレス
+fn stack_addr(sp: u8) -> u16 {
+    binary::u8u8_to_u16(0x01, sp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::fixture::*;
+    use crate::cpu::ram::Ram;
+    use crate::io;
+
+    #[test]
+    fn it_cpu_run() {
+        let mut contents =
+            io::read_to_binary("../docs/demo/sample1.nes").expect("Don't warry, it is debug");
+
+        let ines = crate::ines::parser(&mut contents).unwrap();
+        println!("{:?}", ines);
+        let mut cpu = Cpu::new(ines);
+
+        // HELLO, WORLD!を書き込んだあとはmainloopで無限ループする
+        for _ in 0..200 {
+            cpu.run();
+        }
+        cpu.bus.scheduler.ppu.draw(&cpu.bus.ppu);
+    }
+
+    #[test]
+    fn it_step() {
+        let mut cpu = cpu_with_program(&[
+            0xa2, 0x01, // LDX #$01
+            0x9d, 0x00, 0x02, // STA $0200,X
+            0xbd, 0x00, 0x02, // LDA $0200,X
+        ]);
+        cpu.register.A = 0x12;
+
+        let record = cpu.step().unwrap();
+        assert_eq!(record.interrupt, None);
+        assert_eq!(record.addr, 0x8000);
+        assert_eq!(record.code(), [0xa2, 0x01]);
+        assert_eq!(record.cmd, Command::LDX);
+        assert_eq!(record.mode, Mode::Immediate);
+        assert_eq!(record.operand, Operand::Data(0x01));
+        assert_eq!(record.before.X, 0x00);
+        assert_eq!(record.after.X, 0x01);
+        assert_eq!(record.after.PC, 0x8002);
+        assert!(record.reads.is_empty());
+
+        let record = cpu.step().unwrap();
+        assert_eq!(record.code(), [0x9d, 0x00, 0x02]);
+        assert_eq!(record.operand, Operand::Addr(0x0201));
+        assert_eq!(record.cycles, 5);
+        assert_eq!(record.writes, vec![(0x0201, 0x12)]);
+
+        cpu.register.A = 0x00;
+        let record = cpu.step().unwrap();
+        assert_eq!(record.reads, vec![(0x0201, 0x12)]);
+        assert_eq!(record.before.A, 0x00);
+        assert_eq!(record.after.A, 0x12);
+    }
+
+    #[test]
+    fn it_step_interrupt() {
+        let mut cpu = cpu_with_program(&[0xea]);
+        cpu.register.PC = 0x8000;
+        cpu.set_nmi(true);
+        let record = cpu.step().unwrap();
+        assert_eq!(record.interrupt, Some(Interrupt::NMI));
+        assert_eq!(record.addr, 0x9000);
+        assert_eq!(record.before.PC, 0x8000);
+        // PCの上位、下位、ステータスレジスタをプッシュ
+        assert_eq!(record.writes.len(), 3);
+        assert_eq!(record.reads.len(), 2);
+    }
+
+    #[test]
+    fn it_ppu_nmi() {
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x80, // LDA #$80
+            0x8d, 0x00, 0x20, // STA $2000
+            0xea, // NOP
+        ]);
+        cpu.register.PC = 0x8000;
+        // VBlank中にNMIを有効にすると、次の命令の前にNMIが発生する
+        cpu.bus.ppu.PPUSTATUS = 0x80;
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::NMI));
+        // アクティブなままなので、再び発生しない
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+    }
+
+    #[test]
+    fn it_cycles() {
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x01, // LDA #$01
+            0xbd, 0x00, 0x90, // LDA $9000,X
+            0x9d, 0x00, 0x02, // STA $0200,X
+            0xfe, 0x00, 0x02, // INC $0200,X
+            0xea, // NOP
+        ]);
+        // RESET
+        assert_eq!(cpu.cycles, 7);
+        assert_eq!(cpu.step().unwrap().cycles, 2);
+        assert_eq!(cpu.step().unwrap().cycles, 4);
+        assert_eq!(cpu.step().unwrap().cycles, 5);
+        assert_eq!(cpu.step().unwrap().cycles, 7);
+        assert_eq!(cpu.step().unwrap().cycles, 2);
+        assert_eq!(cpu.cycles, 7 + 2 + 4 + 5 + 7 + 2);
+    }
+
+    #[test]
+    fn it_cycles_page_crossed() {
+        let mut cpu = cpu_with_program(&[
+            0xbd, 0xff, 0x90, // LDA $90FF,X
+            0xb9, 0xff, 0x90, // LDA $90FF,Y
+            0x9d, 0xff, 0x02, // STA $02FF,X
+            0xb1, 0x10, // LDA ($10),Y
+            0xb1, 0x20, // LDA ($20),Y
+        ]);
+        cpu.register.X = 1;
+        cpu.register.Y = 0;
+        cpu.bus.write(0x0010, 0x02).unwrap();
+        cpu.bus.write(0x0011, 0x02).unwrap();
+        cpu.bus.write(0x0020, 0x02).unwrap();
+        cpu.bus.write(0x0021, 0x02).unwrap();
+        assert_eq!(cpu.step().unwrap().cycles, 5);
+        assert_eq!(cpu.step().unwrap().cycles, 4);
+        // 書き込みはページをまたいでも変わらない
+        assert_eq!(cpu.step().unwrap().cycles, 5);
+        assert_eq!(cpu.step().unwrap().cycles, 5);
+        cpu.register.Y = 0xff;
+        assert_eq!(cpu.step().unwrap().cycles, 6);
+    }
+
+    #[test]
+    fn it_cycles_branch() {
+        let mut program = vec![0xeau8; 0x200];
+        // 0x8000 BNE +2 (不成立)
+        program[0x00..0x02].copy_from_slice(&[0xd0, 0x02]);
+        // 0x8002 BEQ +0x6c (成立、同じページ)
+        program[0x02..0x04].copy_from_slice(&[0xf0, 0x6c]);
+        // 0x8070 BEQ +0x7e (成立、同じページ)
+        program[0x70..0x72].copy_from_slice(&[0xf0, 0x7e]);
+        // 0x80f0 BEQ +0x20 (成立、ページをまたぐ)
+        program[0xf0..0xf2].copy_from_slice(&[0xf0, 0x20]);
+        let mut cpu = cpu_with_program(&program);
+        cpu.register.P = cpu.register.P.set_z(true);
+        assert_eq!(cpu.step().unwrap().cycles, 2);
+        assert_eq!(cpu.register.PC, 0x8002);
+        assert_eq!(cpu.step().unwrap().cycles, 3);
+        assert_eq!(cpu.register.PC, 0x8070);
+        assert_eq!(cpu.step().unwrap().cycles, 3);
+        assert_eq!(cpu.register.PC, 0x80f0);
+        assert_eq!(cpu.step().unwrap().cycles, 4);
+        assert_eq!(cpu.register.PC, 0x8112);
+    }
+
+    #[test]
+    fn it_cycles_interrupt() {
+        let mut cpu = cpu_with_program(&[0xea]);
+        cpu.set_nmi(true);
+        // NMIの7サイクル + 0x9000のNOP
+        assert_eq!(cpu.step().unwrap().cycles, 7 + 2);
+        assert_eq!(cpu.register.PC, 0x9001);
+    }
+
+    #[test]
+    fn it_adc() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x50;
+        cpu.adc(Operand::Data(0x10));
+        assert_eq!(cpu.register.A, 0x60);
+        assert!(!cpu.register.P.c());
+        assert!(!cpu.register.P.v());
+
+        cpu.adc(Operand::Data(0x20));
+        assert_eq!(cpu.register.A, 0x80);
+        assert!(cpu.register.P.n());
+        assert!(cpu.register.P.v());
+
+        cpu.adc(Operand::Data(0x80));
+        assert_eq!(cpu.register.A, 0x00);
+        assert!(cpu.register.P.z());
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.v());
+
+        cpu.adc(Operand::Data(0x01));
+        assert_eq!(cpu.register.A, 0x02);
+        assert!(!cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_sbc() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_c(true);
+        cpu.register.A = 0x50;
+        cpu.sbc(Operand::Data(0xb0));
+        assert_eq!(cpu.register.A, 0xa0);
+        assert!(cpu.register.P.n());
+        assert!(cpu.register.P.v());
+        assert!(!cpu.register.P.c());
+
+        cpu.sbc(Operand::Data(0x0f));
+        assert_eq!(cpu.register.A, 0x90);
+        assert!(!cpu.register.P.v());
+        assert!(cpu.register.P.c());
+
+        cpu.sbc(Operand::Data(0x90));
+        assert_eq!(cpu.register.A, 0x00);
+        assert!(cpu.register.P.z());
+        assert!(cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_and() {
+        let mut cpu = cpu();
+        cpu.register.A = 0b1100_1100;
+        cpu.and(Operand::Data(0b1010_1010));
+        assert_eq!(cpu.register.A, 0b1000_1000);
+        assert!(cpu.register.P.n());
+        assert!(!cpu.register.P.z());
+        cpu.and(Operand::Data(0b0111_0111));
+        assert_eq!(cpu.register.A, 0);
+        assert!(!cpu.register.P.n());
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_ora() {
+        let mut cpu = cpu();
+        cpu.ora(Operand::Data(0));
+        assert!(cpu.register.P.z());
+        cpu.register.A = 0b0000_1100;
+        cpu.ora(Operand::Data(0b1010_0000));
+        assert_eq!(cpu.register.A, 0b1010_1100);
+        assert!(cpu.register.P.n());
+        assert!(!cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_eor() {
+        let mut cpu = cpu();
+        cpu.register.A = 0b1100_1100;
+        cpu.eor(Operand::Data(0b1010_1010));
+        assert_eq!(cpu.register.A, 0b0110_0110);
+        assert!(!cpu.register.P.n());
+        cpu.eor(Operand::Data(0b0110_0110));
+        assert_eq!(cpu.register.A, 0);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_asl() {
+        let mut cpu = cpu();
+        cpu.register.A = 0b1100_0001;
+        cpu.asl(Operand::None);
+        assert_eq!(cpu.register.A, 0b1000_0010);
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.n());
+        cpu.register.A = 0b1000_0000;
+        cpu.asl(Operand::None);
+        assert_eq!(cpu.register.A, 0);
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_lsr() {
+        let mut cpu = cpu();
+        cpu.register.A = 0b1000_0011;
+        cpu.lsr(Operand::None);
+        assert_eq!(cpu.register.A, 0b0100_0001);
+        assert!(cpu.register.P.c());
+        assert!(!cpu.register.P.n());
+        cpu.register.A = 0b0000_0001;
+        cpu.lsr(Operand::None);
+        assert_eq!(cpu.register.A, 0);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_rol() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_c(true);
+        cpu.register.A = 0b1000_0000;
+        cpu.rol(Operand::None);
+        assert_eq!(cpu.register.A, 0b0000_0001);
+        assert!(cpu.register.P.c());
+        cpu.rol(Operand::None);
+        assert_eq!(cpu.register.A, 0b0000_0011);
+        assert!(!cpu.register.P.c());
+        cpu.register.A = 0b0100_0000;
+        cpu.rol(Operand::None);
+        assert_eq!(cpu.register.A, 0b1000_0000);
+        assert!(cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_ror() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_c(true);
+        cpu.register.A = 0b0000_0001;
+        cpu.ror(Operand::None);
+        assert_eq!(cpu.register.A, 0b1000_0000);
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.n());
+        cpu.register.P = cpu.register.P.set_c(false);
+        cpu.register.A = 0b0000_0001;
+        cpu.ror(Operand::None);
+        assert_eq!(cpu.register.A, 0);
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_shift_memory() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x01;
+        cpu.bus.write(0x0044, 0b1000_0001).unwrap();
+        cpu.asl(Operand::Addr(0x0044));
+        assert_eq!(cpu.bus.read(0x0044).unwrap(), 0b0000_0010);
+        assert!(cpu.register.P.c());
+        cpu.lsr(Operand::Addr(0x0044));
+        assert_eq!(cpu.bus.read(0x0044).unwrap(), 0b0000_0001);
+        assert!(!cpu.register.P.c());
+        cpu.ror(Operand::Addr(0x0044));
+        assert_eq!(cpu.bus.read(0x0044).unwrap(), 0b0000_0000);
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.z());
+        cpu.rol(Operand::Addr(0x0044));
+        assert_eq!(cpu.bus.read(0x0044).unwrap(), 0b0000_0001);
+        assert!(!cpu.register.P.c());
+        // アキュムレータは変更しない
+        assert_eq!(cpu.register.A, 0x01);
+    }
+
+    #[test]
+    fn it_read_modify_write_dummy_write() {
+        let mut cpu = cpu();
+        cpu.bus.write(0x2006, 0x20).unwrap();
+        cpu.bus.write(0x2006, 0x00).unwrap();
+        cpu.bus.write(0x2007, 0x41).unwrap();
+        cpu.bus.ppu.vram.write(0x2001, 0x10);
+        cpu.inc(Operand::Addr(0x2007));
+        // 読み込みはリードバッファの0x00が返り、元の値と結果の2回書き込む
+        assert_eq!(cpu.bus.ppu.vram.read(0x2001), 0x10);
+        assert_eq!(cpu.bus.ppu.vram.read(0x2002), 0x00);
+        assert_eq!(cpu.bus.ppu.vram.read(0x2003), 0x01);
+        assert_eq!(cpu.bus.ppu.PPUDATA.buffer, 0x10);
+    }
+
+    #[test]
+    fn it_bcc() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.register.P = cpu.register.P.set_c(true);
+        cpu.bcc(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_c(false);
+        cpu.bcc(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bcs() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.bcs(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_c(true);
+        cpu.bcs(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_beq() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.beq(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_z(true);
+        cpu.beq(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bne() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.register.P = cpu.register.P.set_z(true);
+        cpu.bne(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_z(false);
+        cpu.bne(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bvc() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.register.P = cpu.register.P.set_v(true);
+        cpu.bvc(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_v(false);
+        cpu.bvc(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bvs() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.bvs(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_v(true);
+        cpu.bvs(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bpl() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.register.P = cpu.register.P.set_n(true);
+        cpu.bpl(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_n(false);
+        cpu.bpl(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bmi() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8002;
+        cpu.bmi(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8002);
+        cpu.register.P = cpu.register.P.set_n(true);
+        cpu.bmi(Operand::Addr(0x8010));
+        assert_eq!(cpu.register.PC, 0x8010);
+    }
+
+    #[test]
+    fn it_bit() {
+        let mut cpu = cpu();
+        cpu.bus.write(0x0010, 0b1100_0000).unwrap();
+        cpu.register.A = 0b0011_1111;
+        cpu.bit(Operand::Addr(0x0010));
+        assert!(cpu.register.P.n());
+        assert!(cpu.register.P.v());
+        assert!(cpu.register.P.z());
+        assert_eq!(cpu.register.A, 0b0011_1111);
+
+        cpu.bus.write(0x0010, 0b0000_0001).unwrap();
+        cpu.bit(Operand::Addr(0x0010));
+        assert!(!cpu.register.P.n());
+        assert!(!cpu.register.P.v());
+        assert!(!cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_jmp() {
+        let mut cpu = cpu();
+        cpu.jmp(Operand::Addr(0x1234));
+        assert_eq!(cpu.register.PC, 0x1234);
+    }
+
+    #[test]
+    fn it_jsr() {
+        let mut cpu = cpu();
+        // JSR $1234 at 0x8000, fetch後のPCは0x8003
+        cpu.register.PC = 0x8003;
+        cpu.jsr(Operand::Addr(0x1234));
+        assert_eq!(cpu.register.PC, 0x1234);
+        assert_eq!(cpu.bus.read(0x01ff).unwrap(), 0x80);
+        assert_eq!(cpu.bus.read(0x01fe).unwrap(), 0x02);
+    }
+
+    #[test]
+    fn it_rts() {
+        let mut cpu = cpu();
+        cpu.register.PC = 0x8003;
+        cpu.jsr(Operand::Addr(0x1234));
+        cpu.rts();
+        assert_eq!(cpu.register.PC, 0x8003);
+        assert_eq!(cpu.register.SP, 0xff);
+    }
+
+    #[test]
+    fn it_jsr_rts_wrap() {
+        let mut cpu = cpu();
+        // JSRの最後のバイトが$FFFFのとき、fetch後のPCは$0000に回り込む
+        cpu.register.PC = 0x0000;
+        cpu.jsr(Operand::Addr(0x1234));
+        assert_eq!(cpu.bus.read(0x01ff).unwrap(), 0xff);
+        assert_eq!(cpu.bus.read(0x01fe).unwrap(), 0xff);
+        cpu.rts();
+        assert_eq!(cpu.register.PC, 0x0000);
+    }
+
+    #[test]
+    fn it_brk() {
+        let mut cpu = cpu_with_vectors();
+        let sp = cpu.register.SP;
+        // BRK at 0x8000, fetch後のPCは0x8001
+        cpu.register.PC = 0x8001;
+        cpu.brk();
+        assert_eq!(cpu.register.PC, 0xa000);
+        assert!(cpu.register.P.i());
+        assert_eq!(cpu.bus.read(stack_addr(sp)).unwrap(), 0x80);
+        assert_eq!(cpu.bus.read(stack_addr(sp - 1)).unwrap(), 0x02);
+        // B、Rフラグがセットされている
+        assert_eq!(
+            cpu.bus.read(stack_addr(sp - 2)).unwrap() & 0b0011_0000,
+            0b0011_0000
+        );
+        assert_eq!(cpu.register.SP, sp - 3);
+    }
+
+    #[test]
+    fn it_brk_wrap() {
+        let mut cpu = cpu_with_vectors();
+        let sp = cpu.register.SP;
+        // BRK at 0xfffe, fetch後のPCは0xffff
+        cpu.register.PC = 0xffff;
+        cpu.brk();
+        assert_eq!(cpu.register.PC, 0xa000);
+        assert_eq!(cpu.bus.read(stack_addr(sp)).unwrap(), 0x00);
+        assert_eq!(cpu.bus.read(stack_addr(sp - 1)).unwrap(), 0x00);
+    }
+
+    #[test]
+    fn it_rti() {
+        let mut cpu = cpu();
+        cpu.push_stack(0x12);
+        cpu.push_stack(0x34);
+        cpu.push_stack(0b1101_0011);
+        cpu.rti();
+        assert_eq!(cpu.register.PC, 0x1234);
+        // Bフラグは無視し、Rフラグは常に1
+        assert_eq!(u8::from(cpu.register.P), 0b1110_0011);
+        assert_eq!(cpu.register.SP, 0xff);
+    }
+
+    #[test]
+    fn it_reset() {
+        let mut cpu = cpu();
+        cpu.bus = MemoryMap::new(vectors());
+        cpu.register.P = cpu.register.P.set_i(false);
+        cpu.reset();
+        assert_eq!(cpu.register.PC, 0x8000);
+        assert!(cpu.register.P.i());
+        assert_eq!(cpu.register.SP, 0xfc);
+    }
+
+    #[test]
+    fn it_reset_vector_with_16k_program() {
+        let mut program = vec![0xeau8; 0x4000];
+        program[0x3ffc..0x3ffe].copy_from_slice(&[0x34, 0xc2]);
+        let mut cpu = cpu();
+        cpu.bus = MemoryMap::new(program);
+        cpu.reset();
+        assert_eq!(cpu.register.PC, 0xc234);
+    }
+
+    #[test]
+    fn it_nmi() {
+        let mut cpu = cpu_with_vectors();
+        cpu.register.PC = 0x8123;
+        cpu.register.P = cpu.register.P.set_c(true);
+        let sp = cpu.register.SP;
+        cpu.set_nmi(true);
+        // NMIはIフラグに関係なく発生する
+        assert_eq!(cpu.poll_interrupt(), Some(Interrupt::NMI));
+        cpu.interrupt(Interrupt::NMI);
+        assert_eq!(cpu.register.PC, 0x9000);
+        assert_eq!(cpu.bus.read(stack_addr(sp)).unwrap(), 0x81);
+        assert_eq!(cpu.bus.read(stack_addr(sp - 1)).unwrap(), 0x23);
+        // Bフラグはクリア、Rフラグはセット
+        assert_eq!(
+            cpu.bus.read(stack_addr(sp - 2)).unwrap() & 0b0011_0001,
+            0b0010_0001
+        );
+
+        cpu.rti();
+        assert_eq!(cpu.register.PC, 0x8123);
+        assert!(cpu.register.P.c());
+        assert_eq!(cpu.register.SP, sp);
+    }
+
+    #[test]
+    fn it_nmi_edge() {
+        let mut cpu = cpu_with_vectors();
+        cpu.set_nmi(true);
+        assert_eq!(cpu.poll_interrupt(), Some(Interrupt::NMI));
+        // アクティブのままでは再度発生しない
+        cpu.set_nmi(true);
+        assert_eq!(cpu.poll_interrupt(), None);
+        cpu.set_nmi(false);
+        cpu.set_nmi(true);
+        assert_eq!(cpu.poll_interrupt(), Some(Interrupt::NMI));
+    }
+
+    #[test]
+    fn it_irq() {
+        let mut cpu = cpu_with_vectors();
+        cpu.set_irq(true);
+        // リセット直後はIフラグがセットされている
+        assert_eq!(cpu.poll_interrupt(), None);
+        cpu.cli();
+        assert_eq!(cpu.poll_interrupt(), Some(Interrupt::IRQ));
+        cpu.interrupt(Interrupt::IRQ);
+        assert_eq!(cpu.register.PC, 0xa000);
+        assert!(cpu.register.P.i());
+        assert_eq!(cpu.poll_interrupt(), None);
+        cpu.set_irq(false);
+        cpu.cli();
+        assert_eq!(cpu.poll_interrupt(), None);
+    }
+
+    #[test]
+    fn it_cmp() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x40;
+        cpu.cmp(Operand::Data(0x40));
+        assert!(cpu.register.P.z());
+        assert!(cpu.register.P.c());
+        assert!(!cpu.register.P.n());
+        cpu.cmp(Operand::Data(0x41));
+        assert!(!cpu.register.P.z());
+        assert!(!cpu.register.P.c());
+        assert!(cpu.register.P.n());
+        cpu.cmp(Operand::Data(0x01));
+        assert!(!cpu.register.P.z());
+        assert!(cpu.register.P.c());
+        assert!(!cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_cpx() {
+        let mut cpu = cpu();
+        cpu.register.X = 0x10;
+        cpu.cpx(Operand::Data(0x10));
+        assert!(cpu.register.P.z());
+        assert!(cpu.register.P.c());
+        cpu.cpx(Operand::Data(0xff));
+        assert!(!cpu.register.P.z());
+        assert!(!cpu.register.P.c());
+        assert!(!cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_cpy() {
+        let mut cpu = cpu();
+        cpu.register.Y = 0x80;
+        cpu.cpy(Operand::Data(0x00));
+        assert!(!cpu.register.P.z());
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.n());
+        cpu.cpy(Operand::Data(0x80));
+        assert!(cpu.register.P.z());
+        assert!(cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_inc() {
+        let mut cpu = cpu();
+        cpu.bus.write(0x0010, 0x7f).unwrap();
+        cpu.inc(Operand::Addr(0x0010));
+        assert_eq!(cpu.bus.read(0x0010).unwrap(), 0x80);
+        assert!(cpu.register.P.n());
+        cpu.bus.write(0x0010, 0xff).unwrap();
+        cpu.inc(Operand::Addr(0x0010));
+        assert_eq!(cpu.bus.read(0x0010).unwrap(), 0x00);
+        assert!(cpu.register.P.z());
+        assert!(!cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_dec() {
+        let mut cpu = cpu();
+        cpu.bus.write(0x0010, 0x01).unwrap();
+        cpu.dec(Operand::Addr(0x0010));
+        assert_eq!(cpu.bus.read(0x0010).unwrap(), 0x00);
+        assert!(cpu.register.P.z());
+        cpu.dec(Operand::Addr(0x0010));
+        assert_eq!(cpu.bus.read(0x0010).unwrap(), 0xff);
+        assert!(cpu.register.P.n());
+        assert!(!cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_inx() {
+        let mut cpu = cpu();
+        cpu.register.X = 0xff;
+        cpu.inx();
+        assert_eq!(cpu.register.X, 0x00);
+        assert!(cpu.register.P.z());
+        cpu.inx();
+        assert_eq!(cpu.register.X, 0x01);
+        assert!(!cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_dex() {
+        let mut cpu = cpu();
+        cpu.dex();
+        assert_eq!(cpu.register.X, 0xff);
+        assert!(cpu.register.P.n());
+        cpu.register.X = 0x01;
+        cpu.dex();
+        assert_eq!(cpu.register.X, 0x00);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_iny() {
+        let mut cpu = cpu();
+        cpu.register.Y = 0x7f;
+        cpu.iny();
+        assert_eq!(cpu.register.Y, 0x80);
+        assert!(cpu.register.P.n());
+        cpu.register.Y = 0xff;
+        cpu.iny();
+        assert_eq!(cpu.register.Y, 0x00);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_dey() {
+        let mut cpu = cpu();
+        cpu.dey();
+        assert_eq!(cpu.register.Y, 0xff);
+        assert!(cpu.register.P.n());
+        cpu.register.Y = 0x01;
+        cpu.dey();
+        assert_eq!(cpu.register.Y, 0x00);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_clc() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_c(true);
+        cpu.clc();
+        assert!(!cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_sec() {
+        let mut cpu = cpu();
+        cpu.sec();
+        assert!(cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_cli() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_i(true);
+        cpu.cli();
+        assert!(!cpu.register.P.i());
+    }
+
+    #[test]
+    fn it_sei() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_i(false);
+        cpu.sei();
+        assert!(cpu.register.P.i());
+    }
+
+    #[test]
+    fn it_cld() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_d(true);
+        cpu.cld();
+        assert!(!cpu.register.P.d());
+    }
+
+    #[test]
+    fn it_sed() {
+        let mut cpu = cpu();
+        cpu.sed();
+        assert!(cpu.register.P.d());
+    }
+
+    #[test]
+    fn it_clv() {
+        let mut cpu = cpu();
+        cpu.register.P = cpu.register.P.set_v(true);
+        cpu.clv();
+        assert!(!cpu.register.P.v());
+    }
+
+    #[test]
+    fn it_lda() {
+        let mut cpu = cpu();
+        cpu.lda(Operand::Data(0x80));
+        assert_eq!(cpu.register.A, 0x80);
+        assert!(cpu.register.P.n());
+        cpu.bus.write(0x0010, 0x00).unwrap();
+        cpu.lda(Operand::Addr(0x0010));
+        assert_eq!(cpu.register.A, 0x00);
+        assert!(cpu.register.P.z());
+        assert!(!cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_ldx() {
+        let mut cpu = cpu();
+        cpu.ldx(Operand::Data(0x12));
+        assert_eq!(cpu.register.X, 0x12);
+        assert!(!cpu.register.P.z());
+        assert!(!cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_ldy() {
+        let mut cpu = cpu();
+        cpu.ldy(Operand::Data(0x00));
+        assert_eq!(cpu.register.Y, 0x00);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_sta() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x12;
+        cpu.sta(Operand::Addr(0x0010));
+        assert_eq!(cpu.bus.read(0x0010).unwrap(), 0x12);
+    }
+
+    #[test]
+    fn it_stx() {
+        let mut cpu = cpu();
+        cpu.register.X = 0x34;
+        cpu.stx(Operand::Addr(0x6000));
+        assert_eq!(cpu.bus.read(0x6000).unwrap(), 0x34);
+    }
+
+    #[test]
+    fn it_sty() {
+        let mut cpu = cpu();
+        cpu.register.Y = 0x56;
+        cpu.sty(Operand::Addr(0x0010));
+        assert_eq!(cpu.bus.read(0x0010).unwrap(), 0x56);
+    }
+
+    #[test]
+    fn it_tax() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x80;
+        cpu.tax();
+        assert_eq!(cpu.register.X, 0x80);
+        assert!(cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_txa() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x80;
+        cpu.txa();
+        assert_eq!(cpu.register.A, 0x00);
+        assert!(cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_tay() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x01;
+        cpu.tay();
+        assert_eq!(cpu.register.Y, 0x01);
+        assert!(!cpu.register.P.z());
+        assert!(!cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_tya() {
+        let mut cpu = cpu();
+        cpu.register.Y = 0xff;
+        cpu.tya();
+        assert_eq!(cpu.register.A, 0xff);
+        assert!(cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_tsx() {
+        let mut cpu = cpu();
+        cpu.push_stack(0x00);
+        cpu.tsx();
+        assert_eq!(cpu.register.X, 0xfe);
+        assert!(cpu.register.P.n());
+    }
+
+    #[test]
+    fn it_txs() {
+        let mut cpu = cpu();
+        cpu.register.X = 0x00;
+        cpu.txs();
+        assert_eq!(cpu.register.SP, 0x00);
+        // TXSはフラグを変更しない
+        assert!(!cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_pha() {
+        let mut cpu = cpu();
+        cpu.register.A = 0x12;
+        cpu.pha();
+        assert_eq!(cpu.bus.read(0x01ff).unwrap(), 0x12);
+        assert_eq!(cpu.register.SP, 0xfe);
+    }
+
+    #[test]
+    fn it_pla() {
+        let mut cpu = cpu();
+        cpu.push_stack(0x00);
+        cpu.register.A = 0x12;
+        cpu.pla();
+        assert_eq!(cpu.register.A, 0x00);
+        assert!(cpu.register.P.z());
+        assert_eq!(cpu.register.SP, 0xff);
+    }
+
+    #[test]
+    fn it_php() {
+        let mut cpu = cpu();
+        cpu.register.P = StatusRegister::from(0b0000_0001);
+        cpu.php();
+        assert_eq!(cpu.bus.read(0x01ff).unwrap(), 0b0011_0001);
+    }
+
+    #[test]
+    fn it_plp() {
+        let mut cpu = cpu();
+        cpu.push_stack(0b1101_0011);
+        cpu.plp();
+        assert_eq!(u8::from(cpu.register.P), 0b1110_0011);
+    }
+
+    #[test]
+    fn it_nop() {
+        let mut cpu = cpu();
+        let register = cpu.register;
+        cpu.nop(Operand::None);
+        assert_eq!(cpu.register, register);
+    }
+
+    /// アクセスを記録するバス
+    struct RecordingBus {
+        ram: Ram,
+        accesses: Vec<(&'static str, u16, u8)>,
+    }
+
+    impl Bus for RecordingBus {
+        fn read(&mut self, addr: u16) -> Result<u8, EmuError> {
+            let v = self.ram.read(addr)?;
+            self.accesses.push(("read", addr, v));
+            Ok(v)
+        }
+
+        fn write(&mut self, addr: u16, data: u8) -> Result<(), EmuError> {
+            self.accesses.push(("write", addr, data));
+            self.ram.write(addr, data)
+        }
+
+        fn peek(&self, addr: u16) -> u8 {
+            self.ram.peek(addr)
+        }
+    }
+
+    #[test]
+    fn it_flat_ram() {
+        let mut cpu = Cpu::with_bus(Ram::with_data(
+            0x0200,
+            &[
+                0xa9, 0x42, // LDA #$42
+                0x8d, 0x00, 0x30, // STA $3000
+                0xd0, 0xf9, // BNE $0200
+            ],
+        ));
+        cpu.register.PC = 0x0200;
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x3000), 0x42);
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.PC, 0x0200);
+    }
+
+    #[test]
+    fn it_recording_bus() {
+        let mut cpu = Cpu::with_bus(RecordingBus {
+            ram: Ram::with_data(
+                0x0200,
+                &[
+                    0xe6, 0x10, // INC $10
+                ],
+            ),
+            accesses: vec![],
+        });
+        cpu.bus.ram.write(0x0010, 0x7f).unwrap();
+        cpu.register.PC = 0x0200;
+        cpu.step().unwrap();
+        assert_eq!(
+            cpu.bus.accesses,
+            vec![
+                ("read", 0x0200, 0xe6),
+                ("read", 0x0201, 0x10),
+                ("read", 0x0010, 0x7f),
+                ("write", 0x0010, 0x7f),
+                ("write", 0x0010, 0x80)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_exec_from_ram() {
+        // WRAMの$0300とPRG-RAMの$6000にサブルーチンを書き込んで呼び出す
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0xe8, 0x8d, 0x00, 0x03, // LDA #$E8(INX), STA $0300
+            0xa9, 0x60, 0x8d, 0x01, 0x03, // LDA #$60(RTS), STA $0301
+            0x8d, 0x03, 0x60, // STA $6003
+            0xa9, 0xc8, 0x8d, 0x00, 0x60, // LDA #$C8(INY), STA $6000
+            0xa9, 0xd0, 0x8d, 0x01, 0x60, // LDA #$D0(BNE), STA $6001
+            0xa9, 0xfd, 0x8d, 0x02, 0x60, // LDA #$FD(-3), STA $6002
+            0x20, 0x00, 0x03, // JSR $0300
+            0xa0, 0xfe, // LDY #$FE
+            0x20, 0x00, 0x60, // JSR $6000
+        ]);
+        for _ in 0..14 {
+            cpu.step().unwrap();
+        }
+        assert_eq!(cpu.register.X, 0x01);
+        assert_eq!(cpu.register.PC, 0x801f);
+
+        // $6000: INY, BNE $6000(Y=0になるまでループ), $6003: RTS
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.PC, 0x6000);
+        for _ in 0..4 {
+            cpu.step().unwrap();
+        }
+        assert_eq!(cpu.register.Y, 0x00);
+        assert_eq!(cpu.register.PC, 0x6003);
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.PC, 0x8024);
+    }
+
+    /// $0200からcodeを実行するフラットRAMのCPU
+    fn cpu_with_ram(code: &[u8]) -> Cpu<Ram> {
+        let mut cpu = Cpu::with_bus(Ram::with_data(0x0200, code));
+        cpu.register.PC = 0x0200;
+        cpu
+    }
+
+    #[test]
+    fn it_zero_page_indexed_wrap() {
+        // LDA $F0,X
+        let mut cpu = cpu_with_ram(&[0xb5, 0xf0]);
+        cpu.register.X = 0x20;
+        cpu.bus.write(0x0010, 0x12).unwrap();
+        cpu.bus.write(0x0110, 0x34).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x12);
+
+        // LDX $FF,Y
+        let mut cpu = cpu_with_ram(&[0xb6, 0xff]);
+        cpu.register.Y = 0x01;
+        cpu.bus.write(0x0000, 0x56).unwrap();
+        cpu.bus.write(0x0100, 0x78).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.X, 0x56);
+    }
+
+    #[test]
+    fn it_indexed_indirect_wrap() {
+        // LDA ($FE,X) X=1 -> ポインタは$FFと$00
+        let mut cpu = cpu_with_ram(&[0xa1, 0xfe]);
+        cpu.register.X = 0x01;
+        cpu.bus.write(0x00ff, 0x34).unwrap();
+        cpu.bus.write(0x0000, 0x12).unwrap();
+        cpu.bus.write(0x0100, 0x56).unwrap();
+        cpu.bus.write(0x1234, 0x9a).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x9a);
+    }
+
+    #[test]
+    fn it_indirect_indexed() {
+        // LDA ($10),Y ポインタは下位、上位の順
+        let mut cpu = cpu_with_ram(&[0xb1, 0x10]);
+        cpu.register.Y = 0x01;
+        cpu.bus.write(0x0010, 0x34).unwrap();
+        cpu.bus.write(0x0011, 0x12).unwrap();
+        cpu.bus.write(0x1235, 0x9a).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x9a);
+
+        // LDA ($FF),Y ポインタは$FFと$00
+        let mut cpu = cpu_with_ram(&[0xb1, 0xff]);
+        cpu.register.Y = 0x01;
+        cpu.bus.write(0x00ff, 0x34).unwrap();
+        cpu.bus.write(0x0000, 0x12).unwrap();
+        cpu.bus.write(0x0100, 0x56).unwrap();
+        cpu.bus.write(0x1235, 0x9a).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x9a);
+    }
+
+    #[test]
+    fn it_jmp_indirect_page_bug() {
+        // JMP ($10FF) 上位バイトは$1100ではなく$1000から読む
+        let mut cpu = cpu_with_ram(&[0x6c, 0xff, 0x10]);
+        cpu.bus.write(0x10ff, 0x34).unwrap();
+        cpu.bus.write(0x1000, 0x12).unwrap();
+        cpu.bus.write(0x1100, 0x56).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.PC, 0x1234);
+    }
+
+    #[test]
+    fn it_unofficial_opcodes_length() {
+        // 非公式のNOPも正しい長さで読み飛ばす
+        let mut cpu = cpu_with_ram(&[
+            0x1a, // NOP
+            0x80, 0xff, // NOP #$FF
+            0x04, 0xff, // NOP $FF
+            0x14, 0xff, // NOP $FF,X
+            0x0c, 0xff, 0xff, // NOP $FFFF
+            0x1c, 0xff, 0xff, // NOP $FFFF,X
+        ]);
+        let cycles = (0..6)
+            .map(|_| cpu.step().unwrap().cycles)
+            .collect::<Vec<usize>>();
+        assert_eq!(cycles, vec![2, 2, 3, 4, 4, 4]);
+        assert_eq!(cpu.register.PC, 0x020d);
+        assert_eq!(cpu.register, {
+            let mut r = Register::new();
+            r.PC = 0x020d;
+            r
+        });
+    }
+
+    #[test]
+    fn it_lax_sax() {
+        // LAX $10, SAX $11
+        let mut cpu = cpu_with_ram(&[0xa7, 0x10, 0x87, 0x11]);
+        cpu.bus.write(0x0010, 0b1000_1111).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0b1000_1111);
+        assert_eq!(cpu.register.X, 0b1000_1111);
+        assert!(cpu.register.P.n());
+        cpu.register.A = 0b1111_0000;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x0011), 0b1000_0000);
+    }
+
+    #[test]
+    fn it_dcp_isc() {
+        // DCP $10, ISC $11
+        let mut cpu = cpu_with_ram(&[0xc7, 0x10, 0xe7, 0x11]);
+        cpu.register.A = 0x05;
+        cpu.bus.write(0x0010, 0x06).unwrap();
+        cpu.bus.write(0x0011, 0x01).unwrap();
+        assert_eq!(cpu.step().unwrap().cycles, 5);
+        assert_eq!(cpu.bus.peek(0x0010), 0x05);
+        assert!(cpu.register.P.z());
+        assert!(cpu.register.P.c());
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x0011), 0x02);
+        assert_eq!(cpu.register.A, 0x03);
+        assert!(cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_slo_rla_sre_rra() {
+        // SLO $10, RLA $11, SRE $12, RRA $13
+        let mut cpu = cpu_with_ram(&[0x07, 0x10, 0x27, 0x11, 0x47, 0x12, 0x67, 0x13]);
+        cpu.bus.write(0x0010, 0b1000_0001).unwrap();
+        cpu.bus.write(0x0011, 0b0100_0000).unwrap();
+        cpu.bus.write(0x0012, 0b0000_0011).unwrap();
+        cpu.bus.write(0x0013, 0b0000_0010).unwrap();
+
+        cpu.register.A = 0b0001_0000;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x0010), 0b0000_0010);
+        assert_eq!(cpu.register.A, 0b0001_0010);
+        assert!(cpu.register.P.c());
+
+        cpu.register.A = 0b1000_0001;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x0011), 0b1000_0001);
+        assert_eq!(cpu.register.A, 0b1000_0001);
+        assert!(!cpu.register.P.c());
+
+        cpu.register.A = 0b0000_0000;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x0012), 0b0000_0001);
+        assert_eq!(cpu.register.A, 0b0000_0001);
+        assert!(cpu.register.P.c());
+
+        cpu.register.A = 0x10;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x0013), 0b1000_0001);
+        assert_eq!(cpu.register.A, 0x91);
+        assert!(!cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_immediate_unofficial() {
+        // ANC #$80, ALR #$03, ARR #$C0, AXS #$01
+        let mut cpu = cpu_with_ram(&[0x0b, 0x80, 0x4b, 0x03, 0x6b, 0xc0, 0xcb, 0x01]);
+        cpu.register.A = 0xff;
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x80);
+        assert!(cpu.register.P.c());
+        assert!(cpu.register.P.n());
+
+        cpu.register.A = 0xff;
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x01);
+        assert!(cpu.register.P.c());
+
+        cpu.register.A = 0xff;
+        cpu.register.P = cpu.register.P.set_c(false);
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x60);
+        assert!(cpu.register.P.c());
+        assert!(!cpu.register.P.v());
+
+        cpu.register.A = 0x0f;
+        cpu.register.X = 0x03;
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.X, 0x02);
+        assert!(cpu.register.P.c());
+    }
+
+    #[test]
+    fn it_jam() {
+        let mut cpu = cpu_with_program(&[0x02, 0xea]);
+        let record = cpu.step().unwrap();
+        assert_eq!(record.cmd, Command::JAM);
+        assert!(cpu.jammed());
+        assert_eq!(cpu.register.PC, 0x8000);
+
+        // 停止中は割り込みも受け付けない
+        cpu.set_nmi(true);
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.PC, 0x8000);
+
+        cpu.reset();
+        assert!(!cpu.jammed());
+    }
+
+    #[test]
+    fn it_stack_wrap() {
+        let mut cpu = cpu();
+        cpu.register.SP = 0x00;
+        cpu.push_stack(0x12);
+        assert_eq!(cpu.bus.read(0x0100).unwrap(), 0x12);
+        assert_eq!(cpu.register.SP, 0xff);
+        cpu.push_stack(0x34);
+        assert_eq!(cpu.bus.read(0x01ff).unwrap(), 0x34);
+        assert_eq!(cpu.pop_stack(), 0x34);
+        assert_eq!(cpu.pop_stack(), 0x12);
+        assert_eq!(cpu.register.SP, 0x00);
+    }
+
+    #[test]
+    fn it_power_on_sp() {
+        let mut cpu = Cpu::with_bus(MemoryMap::new(vectors()));
+        cpu.reset();
+        assert_eq!(cpu.register.SP, 0xfd);
+    }
+
+    #[test]
+    fn it_oam_dma() {
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x02, // LDA #$02
+            0x8d, 0x14, 0x40, // STA $4014
+            0xa5, 0x00, // LDA $00
+            0x8d, 0x14, 0x40, // STA $4014
+        ]);
+        for i in 0..=0xff {
+            cpu.bus.write(0x0200 + i, i as u8).unwrap();
+        }
+        cpu.bus.write(0x2003, 0x04).unwrap();
+
+        cpu.step().unwrap();
+        // 奇数サイクルから始まる場合は1サイクル余分にかかる
+        assert_eq!(cpu.cycles, 9);
+        let record = cpu.step().unwrap();
+        assert_eq!(record.dma, Some(0x02));
+        assert_eq!(record.cycles, 4 + 514);
+        // OAMADDRから書き込み、1周して戻る
+        assert_eq!(cpu.bus.ppu.OAM.0[0x04], 0x00);
+        assert_eq!(cpu.bus.ppu.OAM.0[0x03], 0xff);
+        assert_eq!(cpu.bus.ppu.OAMADDR, 0x04);
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.cycles % 2, 0);
+        let record = cpu.step().unwrap();
+        assert_eq!(record.cycles, 4 + 513);
+        // DMAの読み書きは命令の記録に含まない
+        assert_eq!(record.writes, vec![(0x4014, 0x00)]);
+    }
+
+    #[test]
+    fn it_open_bus() {
+        let mut cpu = cpu_with_program(&[
+            0xad, 0x00, 0x50, // LDA $5000
+            0xa2, 0x10, // LDX #$10
+            0xbd, 0xf0, 0x3f, // LDA $3FF0,X
+            0xad, 0x16, 0x20, // LDA $2016
+        ]);
+        cpu.fault_policy = FaultPolicy::Break;
+        // 直前に読み込んだオペランドの上位バイトが見える
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x50);
+        cpu.step().unwrap();
+        // $4000は書き込み専用のAPUレジスタ
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0x3f);
+        // 書き込み専用のPPUレジスタ($2006のミラー)はPPUのI/Oラッチ
+        cpu.bus.write(0x2000, 0xa5).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.A, 0xa5);
+    }
+
+    #[test]
+    fn it_decode_cache() {
+        use crate::cpu::memory_map::{Device, Region};
+
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x01, // LDA #$01
+            0x4c, 0x00, 0x80, // JMP $8000
+        ]);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        // 2周目はキャッシュから実行する
+        cpu.register.A = 0x00;
+        let record = cpu.step().unwrap();
+        assert_eq!(record.code(), [0xa9, 0x01]);
+        assert_eq!(cpu.register.A, 0x01);
+        assert_eq!(cpu.register.PC, 0x8002);
+
+        // $8000～$9FFFをPRG-RAMに切り替えると、キャッシュは使われない
+        cpu.bus.ram[..2].copy_from_slice(&[0xa2, 0x02]); // LDX #$02
+        cpu.bus
+            .map(Region::new(0x8000, 0x9fff, 0x2000, Device::Ram));
+        cpu.register.PC = 0x8000;
+        cpu.step().unwrap();
+        assert_eq!(cpu.register.X, 0x02);
+    }
+
+    #[test]
+    fn it_power_cycle() {
+        let mut cpu = cpu_with_program(&[]);
+        cpu.bus.write(0x0010, 0x12).unwrap();
+        cpu.bus.write(0x2000, 0x80).unwrap();
+        cpu.register.A = 0x34;
+        cpu.register.P = cpu.register.P.set_i(false);
+
+        // リセットボタンではWRAMとレジスタが残る
+        let sp = cpu.register.SP;
+        cpu.reset();
+        assert_eq!(cpu.bus.peek(0x0010), 0x12);
+        assert_eq!(cpu.register.A, 0x34);
+        assert_eq!(cpu.register.SP, sp - 3);
+        assert!(cpu.register.P.i());
+        assert_eq!(cpu.register.PC, 0x8000);
+
+        cpu.power_on = PowerOnPolicy::Fceux;
+        cpu.power_cycle();
+        assert_eq!(cpu.bus.peek(0x0010), 0x00);
+        assert_eq!(cpu.bus.peek(0x0014), 0xff);
+        assert_eq!(cpu.bus.ppu.PPUCTRL.0, 0x00);
+        assert_eq!(cpu.register.A, 0x00);
+        assert_eq!(cpu.register.SP, 0xfd);
+        assert_eq!(cpu.register.PC, 0x8000);
+        assert_eq!(cpu.cycles, 7);
+
+        cpu.power_on = PowerOnPolicy::Ff;
+        cpu.power_cycle();
+        assert_eq!(cpu.bus.peek(0x07ff), 0xff);
+        assert_eq!(cpu.register.A, 0xff);
+    }
+
+    #[test]
+    fn it_read_modify_write_data() {
+        let mut cpu = cpu();
+        cpu.fault_policy = FaultPolicy::Break;
+        cpu.register.A = 0x12;
+        cpu.data_bus = 0x34;
+        cpu.inc(Operand::Data(0x56));
+        assert_eq!(
+            cpu.fault,
+            Some(Fault {
+                error: EmuError::UnexpectedOperand(Operand::Data(0x56)),
+                pc: cpu.current
+            })
+        );
+        // パニックせず、アキュムレータも変更しない
+        assert_eq!(cpu.register.A, 0x12);
+        assert!(!cpu.register.P.z());
+    }
+
+    #[test]
+    fn it_fault_break() {
+        let mut cpu = cpu_with_program(&[
+            0x8d, 0x00, 0x80, // STA $8000
+            0x8d, 0x02, 0x20, // STA $2002
+        ]);
+        cpu.fault_policy = FaultPolicy::Break;
+        cpu.register.A = 0x12;
+        assert_eq!(
+            cpu.step(),
+            Err(Fault {
+                error: EmuError::WriteToRom {
+                    addr: 0x8000,
+                    data: 0x12
+                },
+                pc: 0x8000
+            })
+        );
+        // 書き込みは捨てられ、命令は最後まで実行されている
+        assert_eq!(cpu.bus.peek(0x8000), 0x8d);
+        assert_eq!(cpu.register.PC, 0x8003);
+
+        assert_eq!(
+            cpu.step(),
+            Err(Fault {
+                error: EmuError::WriteReadOnly {
+                    addr: 0x2002,
+                    data: 0x12
+                },
+                pc: 0x8003
+            })
+        );
+        // Faultを返した命令も履歴に残る
+        assert_eq!(
+            cpu.history.iter().map(|r| r.addr).collect::<Vec<u16>>(),
+            vec![0x8000, 0x8003]
+        );
+    }
+
+    #[test]
+    fn it_reuse_access_buffers() {
+        let mut cpu = cpu_with_program(&[0xee, 0x00, 0x02].repeat(8)); // INC $0200
+        cpu.history = History::new(1);
+        // 履歴がいっぱいになるまでは確保する
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        for _ in 0..4 {
+            // 捨てた命令のバッファがそのまま次の命令の記録になる
+            assert!(cpu.reads.capacity() > 0 && cpu.writes.capacity() > 0);
+            let (reads, writes) = (cpu.reads.as_ptr(), cpu.writes.as_ptr());
+            let record = cpu.step().unwrap();
+            assert_eq!((record.reads.len(), record.writes.len()), (1, 2));
+            assert_eq!(record.reads.as_ptr(), reads);
+            assert_eq!(record.writes.as_ptr(), writes);
+        }
+    }
+
+    #[test]
+    fn it_fault_ignore() {
+        let mut cpu = cpu_with_program(&[
+            0x8d, 0x00, 0x80, // STA $8000
+            0xad, 0x00, 0x50, // LDA $5000
+        ]);
+        cpu.fault_policy = FaultPolicy::Ignore;
+        assert!(cpu.step().is_ok());
+        assert_eq!(cpu.bus.peek(0x8000), 0x8d);
+        // 何もつながっていないアドレスはオープンバス(アドレスの上位バイト)が読める
+        assert!(cpu.step().is_ok());
+        assert_eq!(cpu.register.A, 0x50);
+    }
+
+    #[test]
+    fn it_fault_jam() {
+        let mut cpu = cpu_with_program(&[0x02]);
+        cpu.fault_policy = FaultPolicy::Break;
+        assert_eq!(
+            cpu.step(),
+            Err(Fault {
+                error: EmuError::Jammed { addr: 0x8000 },
+                pc: 0x8000
+            })
+        );
+        // 停止したあとは何度実行してもFaultにならない
+        assert!(cpu.step().is_ok());
+    }
 }
@@ -0,0 +1,109 @@
+use crate::ines::program::Program;
+
+/// 既定のエントリ数
+pub const DECODE_CACHE_SIZE: usize = 0x1000;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Entry {
+    pc: u16,
+    /// Bus::bankが返したバンク
+    bank: u32,
+    program: Program,
+    /// オペコードとオペランド
+    bytes: [u8; 3],
+}
+
+/// デコード済みの命令のキャッシュ
+/// PCの下位ビットで引くダイレクトマップ方式で、PCとバンクが一致したときだけ使う。
+/// バンク切り替えはバンクの違いとして、書き込みはinvalidateで取り除く。
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DecodeCache {
+    entries: Vec<Option<Entry>>,
+}
+
+impl DecodeCache {
+    /// sizeは2のべき乗にしてください。0のときは何もキャッシュしません。
+    pub fn new(size: usize) -> Self {
+        DecodeCache {
+            entries: vec![None; size],
+        }
+    }
+
+    fn index(&self, pc: u16) -> Option<usize> {
+        match self.entries.len() {
+            0 => None,
+            size => Some(pc as usize & (size - 1)),
+        }
+    }
+
+    /// 命令とそのバイト列、バイト数
+    /// バイト列の後ろの使わない部分は0になっている。
+    pub fn get(&self, pc: u16, bank: u32) -> Option<(Program, [u8; 3], usize)> {
+        let entry = self.entries[self.index(pc)?].as_ref()?;
+        if entry.pc == pc && entry.bank == bank {
+            Some((entry.program, entry.bytes, entry.program.orderset.length))
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, pc: u16, bank: u32, program: Program, bytes: &[u8]) {
+        if let Some(i) = self.index(pc) {
+            let mut entry = Entry {
+                pc,
+                bank,
+                program,
+                bytes: [0; 3],
+            };
+            entry.bytes[..bytes.len()].copy_from_slice(bytes);
+            self.entries[i] = Some(entry);
+        }
+    }
+
+    /// addrを含む命令を取り除きます。
+    pub fn invalidate(&mut self, addr: u16) {
+        for offset in 0..3 {
+            let pc = addr.wrapping_sub(offset);
+            if let Some(i) = self.index(pc) {
+                if let Some(entry) = self.entries[i] {
+                    if entry.pc == pc && (offset as usize) < entry.program.orderset.length {
+                        self.entries[i] = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        DecodeCache::new(DECODE_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decode_cache() {
+        let bytes = [0xad, 0x00, 0x02]; // LDA $0200
+        let program = Program::parse(&bytes, 0);
+        let mut cache = DecodeCache::new(0x10);
+        cache.insert(0x8000, 0, program, &bytes);
+        assert_eq!(cache.get(0x8000, 0), Some((program, bytes, 3)));
+        // バンクが切り替わった
+        assert_eq!(cache.get(0x8000, 1), None);
+        // 同じエントリを使う別のPC
+        assert_eq!(cache.get(0x8010, 0), None);
+
+        cache.invalidate(0x8003);
+        assert!(cache.get(0x8000, 0).is_some());
+        cache.invalidate(0x8002);
+        assert_eq!(cache.get(0x8000, 0), None);
+
+        let mut cache = DecodeCache::new(0);
+        cache.insert(0x8000, 0, program, &bytes);
+        assert_eq!(cache.get(0x8000, 0), None);
+    }
+}
@@ -0,0 +1,78 @@
+use super::interrupt::Interrupt;
+use super::register::Register;
+use crate::ines::program::{Command, Mode, Operand};
+
+/// Cpu::stepで実行した1命令の記録
+/// トレーサーやデバッガ、テストはstdoutではなくこれを参照する。
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExecutionRecord {
+    /// 命令の前に処理した割り込み
+    pub interrupt: Option<Interrupt>,
+    /// 命令の先頭アドレス
+    pub addr: u16,
+    /// オペコードとオペランドのバイト列
+    /// 先頭のlengthバイトだけを使い、残りは0になっている。
+    pub bytes: [u8; 3],
+    pub length: usize,
+    pub cmd: Command,
+    pub mode: Mode,
+    /// インデックスレジスタや間接参照を解決したオペランド
+    pub operand: Operand,
+    /// 割り込みを含めてかかったクロックサイクル数
+    pub cycles: usize,
+    /// 命令の実行中に読み込んだ(アドレス, 値)
+    pub reads: Vec<(u16, u8)>,
+    /// 命令の実行中に書き込んだ(アドレス, 値)
+    pub writes: Vec<(u16, u8)>,
+    /// 命令の後に行ったOAM DMAの転送元ページ
+    /// cyclesにはDMAで停止したサイクル数も含む。
+    pub dma: Option<u8>,
+    /// 実行前のレジスタ
+    pub before: Register,
+    /// 実行後のレジスタ
+    pub after: Register,
+}
+
+impl ExecutionRecord {
+    /// 命令のバイト列
+    pub fn code(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+}
+
+impl std::fmt::Display for ExecutionRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(interrupt) = self.interrupt {
+            writeln!(f, "[Interrupt]{:?}", interrupt)?;
+        }
+        let bytes = self
+            .code()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<String>>();
+        writeln!(
+            f,
+            "[Exec]@{:#06x} {:<8} => {:?}, {} {} ({} cycles)",
+            self.addr,
+            bytes.join(" "),
+            self.cmd,
+            self.mode,
+            self.operand,
+            self.cycles
+        )?;
+        if self.cmd == Command::JAM {
+            writeln!(f, "[Halt]CPU jammed at {:#06x}", self.addr)?;
+        }
+        for (addr, v) in self.reads.iter() {
+            writeln!(f, "[Read]{:#06x} => {:#04x}", addr, v)?;
+        }
+        for (addr, v) in self.writes.iter() {
+            writeln!(f, "[Write]{:#06x} <= {:#04x}", addr, v)?;
+        }
+        if let Some(page) = self.dma {
+            writeln!(f, "[DMA]OAM <= {:#06x}", (page as u16) << 8)?;
+        }
+        writeln!(f, "[Before]\n{}", self.before)?;
+        write!(f, "[After]\n{}", self.after)
+    }
+}
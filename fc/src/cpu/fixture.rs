@@ -0,0 +1,36 @@
+//! テスト用のCPU
+
+use super::memory_map::MemoryMap;
+use super::Cpu;
+
+/// 命令単体のテスト用にROMなしのCPUを作る
+/// スタックは空($01FFから積む)
+pub(crate) fn cpu() -> Cpu {
+    let mut cpu = Cpu::with_bus(MemoryMap::new(vec![]));
+    cpu.register.SP = 0xff;
+    cpu
+}
+
+/// 割り込みベクタだけを書き込んだ16KBのPRG-ROM
+/// $8000と$C000にミラーされる。
+/// NMI: 0x9000, RESET: 0x8000, IRQ/BRK: 0xa000
+pub(crate) fn vectors() -> Vec<u8> {
+    let mut program = vec![0xeau8; 0x4000];
+    program[0x3ffa..].copy_from_slice(&[0x00, 0x90, 0x00, 0x80, 0x00, 0xa0]);
+    program
+}
+
+/// 0x8000から始まるプログラムを読み込んでRESETしたCPU
+/// プログラムは0xc000にもミラーされる。
+pub(crate) fn cpu_with_program(code: &[u8]) -> Cpu {
+    let mut program = vectors();
+    program[..code.len()].copy_from_slice(code);
+    let mut cpu = cpu();
+    cpu.bus = MemoryMap::new(program);
+    cpu.reset();
+    cpu
+}
+
+pub(crate) fn cpu_with_vectors() -> Cpu {
+    cpu_with_program(&[])
+}
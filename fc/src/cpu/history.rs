@@ -0,0 +1,100 @@
+use super::execution_record::ExecutionRecord;
+use std::collections::VecDeque;
+
+/// 直近に実行した命令を残しておくリングバッファ
+/// クラッシュレポートで、止まるまでに何を実行していたかを表示するために使う。
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct History {
+    records: VecDeque<ExecutionRecord>,
+    capacity: usize,
+}
+
+/// 既定で残しておく命令の数
+pub const HISTORY_SIZE: usize = 64;
+
+impl History {
+    /// capacityが0のときは何も残しません。
+    pub fn new(capacity: usize) -> Self {
+        History {
+            records: VecDeque::with_capacity(capacity.max(1)),
+            capacity,
+        }
+    }
+
+    /// 命令を受け取って残し、その参照と、いっぱいだったときに捨てた一番古い命令を返します。
+    /// Cpuは捨てた命令の読み書きの記録のバッファを次の命令で使い回します。
+    /// capacityが0でも、Cpu::stepの戻り値にするため直前の1命令だけは持っておきます。
+    pub fn push(&mut self, record: ExecutionRecord) -> (&ExecutionRecord, Option<ExecutionRecord>) {
+        let evicted = if self.records.len() >= self.capacity.max(1) {
+            self.records.pop_front()
+        } else {
+            None
+        };
+        self.records.push_back(record);
+        (&self.records[self.records.len() - 1], evicted)
+    }
+
+    /// 直前に残した命令
+    pub fn last(&self) -> Option<&ExecutionRecord> {
+        self.records.back()
+    }
+
+    /// 古い順
+    pub fn iter(&self) -> impl Iterator<Item = &ExecutionRecord> {
+        let skip = self.records.len().saturating_sub(self.capacity);
+        self.records.iter().skip(skip)
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(HISTORY_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::register::Register;
+    use crate::ines::program::{Command, Mode, Operand};
+
+    fn record(addr: u16) -> ExecutionRecord {
+        ExecutionRecord {
+            interrupt: None,
+            addr,
+            bytes: [0xea, 0x00, 0x00],
+            length: 1,
+            cmd: Command::NOP,
+            mode: Mode::Implied,
+            operand: Operand::None,
+            cycles: 2,
+            reads: vec![],
+            writes: vec![],
+            dma: None,
+            before: Register::new(),
+            after: Register::new(),
+        }
+    }
+
+    #[test]
+    fn it_history() {
+        let mut history = History::new(2);
+        for addr in 0..2 {
+            let (last, evicted) = history.push(record(addr));
+            assert_eq!(last.addr, addr);
+            assert_eq!(evicted, None);
+        }
+        let (last, evicted) = history.push(record(2));
+        assert_eq!(last.addr, 2);
+        assert_eq!(evicted.map(|r| r.addr), Some(0));
+        assert_eq!(
+            history.iter().map(|r| r.addr).collect::<Vec<u16>>(),
+            vec![1, 2]
+        );
+
+        let mut history = History::new(0);
+        assert_eq!(history.push(record(0)).0.addr, 0);
+        assert_eq!(history.iter().count(), 0);
+        assert_eq!(history.push(record(1)).1.map(|r| r.addr), Some(0));
+    }
+}
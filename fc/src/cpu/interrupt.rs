@@ -0,0 +1,33 @@
+/// 0xFFFA～0xFFFB NMIベクタ
+pub const NMI_VECTOR: u16 = 0xFFFA;
+/// 0xFFFC～0xFFFD RESETベクタ
+pub const RESET_VECTOR: u16 = 0xFFFC;
+/// 0xFFFE～0xFFFF IRQ、BRKベクタ
+pub const IRQ_VECTOR: u16 = 0xFFFE;
+
+/// reference: https://wiki.nesdev.org/w/index.php/CPU_interrupts
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Interrupt {
+    /// 電源投入時、リセットボタンが押されたとき
+    RESET,
+    /// ノンマスカブル割り込み
+    /// NESではPPUのVBlank開始時に発生する
+    NMI,
+    /// マスカブル割り込み
+    /// Iフラグがセットされていれば無視する
+    IRQ,
+    /// BRK命令によるソフトウェア割り込み
+    BRK,
+}
+
+impl Interrupt {
+    /// 割り込みベクタのアドレス
+    pub fn vector(&self) -> u16 {
+        match self {
+            Interrupt::RESET => RESET_VECTOR,
+            Interrupt::NMI => NMI_VECTOR,
+            Interrupt::IRQ => IRQ_VECTOR,
+            Interrupt::BRK => IRQ_VECTOR,
+        }
+    }
+}
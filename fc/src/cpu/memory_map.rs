@@ -1,15 +1,151 @@
-use crate::binary::DisplayBinary;
+use super::bus::Bus;
+use crate::binary::{self, DisplayBinary};
+use crate::error::EmuError;
 use crate::ppu::io_register::IORegister;
-use std::vec::*;
+use crate::scheduler::Scheduler;
 
 const WRAM_RANGE: std::ops::Range<usize> = 0x0000..0x0800;
-const WRAM_MIRROR_RANGE: std::ops::Range<usize> = 0x0800..0x2000;
 const PPU_REGISTER_RANGE: std::ops::Range<usize> = 0x2000..0x2008;
-const PPU_MIRROR_RANGE: std::ops::Range<usize> = 0x2008..0x4000;
 const APU_RANGE: std::ops::Range<usize> = 0x4000..0x4020;
-const ROM_RANGE: std::ops::Range<usize> = 0x4020..0x6000;
+/// APUとI/Oレジスタのうち読み込めるもの($4015、$4016、$4017)の位置
+/// 残りは書き込み専用か、何もつながっていない。
+const APU_READABLE_RANGE: std::ops::RangeInclusive<usize> = 0x15..=0x17;
+const UNMAPPED_RANGE: std::ops::Range<usize> = 0x4020..0x6000;
 const RAM_RANGE: std::ops::Range<usize> = 0x6000..0x8000;
 const PRG_ROM_RANGE: std::ops::Range<usize> = 0x8000..0x10000;
+const PRG_ROM_MIRROR_SIZE: usize = 0x4000;
+const PAGE_COUNT: usize = 0x100;
+
+/// アドレスの振り分け先
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Device {
+    /// 0x0000～0x07FF WRAM
+    Wram,
+    /// 0x2000～0x2007 PPUレジスタ
+    PpuRegister,
+    /// 0x4000～0x401F APU、I/Oレジスタ
+    Apu,
+    /// 何もつながっていない
+    /// 読み込みはオープンバスになり、書き込みは無視される。
+    OpenBus,
+    /// 0x6000～0x7FFF 拡張RAM(バッテリーバックアップ)
+    Ram,
+    /// 0x8000～0xFFFF PRG-ROM
+    PrgRom,
+}
+
+/// アドレスデコーダの1行
+/// start～endへのアクセスを、deviceの(addr - start) % size番目に振り分ける。
+/// sizeが領域より小さい場合は、sizeごとに同じ内容(ミラー)が見える。
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Region {
+    pub start: u16,
+    /// 領域の最後のアドレス(この値を含む)
+    pub end: u16,
+    pub size: usize,
+    pub device: Device,
+}
+
+impl Region {
+    pub fn new(start: u16, end: u16, size: usize, device: Device) -> Self {
+        Region {
+            start,
+            end,
+            size,
+            device,
+        }
+    }
+
+    fn offset(&self, addr: u16) -> Option<usize> {
+        if self.start <= addr && addr <= self.end {
+            Some((addr - self.start) as usize % self.size)
+        } else {
+            None
+        }
+    }
+}
+
+/// 256バイトのページ1つ分の振り分け先
+/// ページ内のアドレスの位置は(base + 下位8ビット) & maskで求まる。
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Page {
+    device: Device,
+    base: usize,
+    mask: usize,
+}
+
+impl Page {
+    /// ページの先頭を含む領域から作ります。
+    /// ページが複数の領域にまたがるなど、この形で表せない場合はNoneを返します。
+    fn new(regions: &[Region], page: u8) -> Option<Self> {
+        let start = binary::u8u8_to_u16(page, 0x00);
+        let end = binary::u8u8_to_u16(page, 0xff);
+        let i = regions.iter().position(|r| r.offset(start).is_some())?;
+        let region = regions[i];
+        // 手前の領域が一部を上書きしている
+        if regions[..i]
+            .iter()
+            .any(|r| r.start <= end && start <= r.end)
+        {
+            return None;
+        }
+        let mask = if region.size.is_power_of_two() {
+            region.size - 1
+        } else {
+            usize::MAX
+        };
+        let page = Page {
+            device: region.device,
+            base: region.offset(start)?,
+            mask,
+        };
+        let covered = (start..=end).all(|addr| region.offset(addr) == Some(page.decode(addr).1));
+        if covered {
+            Some(page)
+        } else {
+            None
+        }
+    }
+
+    fn decode(&self, addr: u16) -> (Device, usize) {
+        let lower = binary::lower_only(addr) as usize;
+        (self.device, (self.base + lower) & self.mask)
+    }
+}
+
+/// 先頭から順に領域を探し、最初に見つかった領域に振り分ける
+fn search(regions: &[Region], addr: u16) -> (Device, usize) {
+    regions
+        .iter()
+        .find_map(|r| r.offset(addr).map(|offset| (r.device, offset)))
+        .expect("the address decoder covers the whole address space")
+}
+
+fn pages(regions: &[Region]) -> [Option<Page>; PAGE_COUNT] {
+    let mut pages = [None; PAGE_COUNT];
+    for (i, page) in pages.iter_mut().enumerate() {
+        *page = Page::new(regions, i as u8);
+    }
+    pages
+}
+
+/// reference: https://wiki.nesdev.org/w/index.php/CPU_memory_map
+/// 0x0800～0x1FFFはWRAMの、0x2008～0x3FFFはPPUレジスタのミラー
+fn regions() -> Vec<Region> {
+    vec![
+        Region::new(0x0000, 0x1fff, WRAM_RANGE.len(), Device::Wram),
+        Region::new(
+            0x2000,
+            0x3fff,
+            PPU_REGISTER_RANGE.len(),
+            Device::PpuRegister,
+        ),
+        Region::new(0x4000, 0x401f, APU_RANGE.len(), Device::Apu),
+        Region::new(0x4020, 0x5fff, UNMAPPED_RANGE.len(), Device::OpenBus),
+        Region::new(0x6000, 0x7fff, RAM_RANGE.len(), Device::Ram),
+        Region::new(0x8000, 0xffff, PRG_ROM_RANGE.len(), Device::PrgRom),
+    ]
+}
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct MemoryMap {
@@ -18,86 +154,252 @@ pub struct MemoryMap {
     /// 上位アドレスは0x01に固定
     /// 0x0100～0x01FF
     pub wram: [u8; WRAM_RANGE.end - WRAM_RANGE.start],
-    pub wram_mirror: [u8; WRAM_MIRROR_RANGE.end - WRAM_MIRROR_RANGE.start],
     pub ppu: IORegister,
-    pub ppu_mirror: [u8; PPU_MIRROR_RANGE.end - PPU_MIRROR_RANGE.start],
+    /// CPUのサイクルに合わせてPPUを進める
+    pub scheduler: Scheduler,
     pub apu: [u8; APU_RANGE.end - APU_RANGE.start],
-    pub rom: [u8; ROM_RANGE.end - ROM_RANGE.start],
     pub ram: [u8; RAM_RANGE.end - RAM_RANGE.start],
     pub prg_rom: [u8; PRG_ROM_RANGE.end - PRG_ROM_RANGE.start],
+    /// アドレスデコーダ
+    /// 先頭から順に探し、最初に見つかった領域に振り分ける。
+    regions: Vec<Region>,
+    /// regionsから作った、上位8ビットで引く振り分け先の表
+    /// 1つのページが複数の領域にまたがる場合はNoneで、regionsを探す。
+    pages: [Option<Page>; PAGE_COUNT],
+    /// 領域を登録するたびに増える
+    /// 命令のキャッシュにバンク切り替えを知らせるために使う。
+    bank: u32,
 }
 
 impl MemoryMap {
     pub fn new(program: Vec<u8>) -> Self {
         let mut m = MemoryMap {
             wram: [0u8; WRAM_RANGE.end - WRAM_RANGE.start],
-            wram_mirror: [0u8; WRAM_MIRROR_RANGE.end - WRAM_MIRROR_RANGE.start],
             ppu: IORegister::default(),
-            ppu_mirror: [0u8; PPU_MIRROR_RANGE.end - PPU_MIRROR_RANGE.start],
+            scheduler: Scheduler::default(),
             apu: [0u8; APU_RANGE.end - APU_RANGE.start],
-            rom: [0u8; ROM_RANGE.end - ROM_RANGE.start],
             ram: [0u8; RAM_RANGE.end - RAM_RANGE.start],
             prg_rom: [0u8; PRG_ROM_RANGE.end - PRG_ROM_RANGE.start],
+            regions: regions(),
+            pages: [None; PAGE_COUNT],
+            bank: 0,
         };
-        for i in 0..program.len() {
-            m.prg_rom[i] = program[i];
+        m.pages = pages(&m.regions);
+        if program.len() > m.prg_rom.len() {
+            // バンク切り替えはまだないので、最初と最後の16KBのバンクを見せる
+            let (first, last) = m.prg_rom.split_at_mut(PRG_ROM_MIRROR_SIZE);
+            first.copy_from_slice(&program[..PRG_ROM_MIRROR_SIZE]);
+            last.copy_from_slice(&program[program.len() - PRG_ROM_MIRROR_SIZE..]);
+        } else {
+            m.prg_rom[..program.len()].copy_from_slice(&program);
+        }
+        // PRG-ROMが16KBのときは0xC000～0xFFFFに0x8000～0xBFFFのミラーが見える
+        if program.len() == PRG_ROM_MIRROR_SIZE {
+            m.map(Region::new(
+                0x8000,
+                0xffff,
+                PRG_ROM_MIRROR_SIZE,
+                Device::PrgRom,
+            ));
         }
         m
     }
 
-    pub fn read(&self, p: u16) -> u8 {
-        let p = p as usize;
-        if WRAM_RANGE.contains(&p) {
-            self.wram[p - WRAM_RANGE.start]
-        } else if WRAM_MIRROR_RANGE.contains(&p) {
-            self.wram_mirror[p - WRAM_MIRROR_RANGE.start]
-        } else if PPU_REGISTER_RANGE.contains(&p) {
-            self.ppu.read(p as u16)
-        } else if PPU_MIRROR_RANGE.contains(&p) {
-            self.ppu_mirror[p - PPU_MIRROR_RANGE.start]
-        } else if APU_RANGE.contains(&p) {
-            self.apu[p - APU_RANGE.start]
-        } else if ROM_RANGE.contains(&p) {
-            self.rom[p - ROM_RANGE.start]
-        } else if RAM_RANGE.contains(&p) {
-            self.ram[p - RAM_RANGE.start]
-        } else if PRG_ROM_RANGE.contains(&p) {
-            self.prg_rom[p - PRG_ROM_RANGE.start]
-        } else {
-            panic!("???")
+    /// 領域を登録します。
+    /// 既存の領域より優先されるので、カートリッジはその一部を上書きできます。
+    pub fn map(&mut self, region: Region) {
+        self.regions.insert(0, region);
+        self.pages = pages(&self.regions);
+        self.bank = self.bank.wrapping_add(1);
+    }
+
+    /// アドレスを振り分け先と、その中での位置に変換します。
+    pub fn decode(&self, addr: u16) -> (Device, usize) {
+        match self.pages[binary::upper_only(addr) as usize] {
+            Some(page) => page.decode(addr),
+            None => search(&self.regions, addr),
         }
     }
 
-    pub fn write(&mut self, p: u16, data: u8) {
-        let p = p as usize;
-        if WRAM_RANGE.contains(&p) {
-            self.wram[p - WRAM_RANGE.start] = data;
-        } else if WRAM_MIRROR_RANGE.contains(&p) {
-            self.wram_mirror[p - WRAM_MIRROR_RANGE.start] = data;
-        } else if PPU_REGISTER_RANGE.contains(&p) {
-            self.ppu.write(p as u16, data);
-        } else if PPU_MIRROR_RANGE.contains(&p) {
-            self.ppu_mirror[p - PPU_MIRROR_RANGE.start] = data;
-        } else if APU_RANGE.contains(&p) {
-            self.apu[p - APU_RANGE.start] = data;
-        } else if ROM_RANGE.contains(&p) {
-            panic!("this is ROM");
-            // self.rom[p - ROM_RANGE.start] = data;
-        } else if RAM_RANGE.contains(&p) {
-            self.ram[p - RAM_RANGE.start] = data;
-        } else if PRG_ROM_RANGE.contains(&p) {
-            panic!("this is ROM");
-            // self.prg_rom[p - PRG_ROM_RANGE.start] = data;
-        } else {
-            panic!("???")
+    /// どの機器もデータバスを駆動しない場合は、EmuError::OpenBusを返します。
+    pub fn read(&mut self, p: u16) -> Result<u8, EmuError> {
+        match self.decode(p) {
+            // PPUレジスタは読み込むとI/Oラッチが変わる
+            (Device::PpuRegister, offset) => Ok(self.ppu.read(ppu_register(offset))),
+            (device, offset) => self.load(p, device, offset),
         }
     }
 
-    pub fn stack<'b>(&'b self, sp: u16) -> DisplayBinary<'b> {
-        DisplayBinary(&self.wram[(sp as usize)..0x0200])
+    /// I/Oレジスタを読み込んでも状態を変えません。
+    /// オープンバスの値はCPUが持っているので、0を返します。
+    pub fn peek(&self, p: u16) -> u8 {
+        let (device, offset) = self.decode(p);
+        self.load(p, device, offset).unwrap_or_default()
+    }
+
+    fn load(&self, p: u16, device: Device, offset: usize) -> Result<u8, EmuError> {
+        match device {
+            Device::Wram => Ok(self.wram[offset]),
+            Device::PpuRegister => Ok(self.ppu.peek(ppu_register(offset))),
+            Device::Apu if APU_READABLE_RANGE.contains(&offset) => Ok(self.apu[offset]),
+            Device::Apu | Device::OpenBus => Err(EmuError::OpenBus { addr: p }),
+            Device::Ram => Ok(self.ram[offset]),
+            Device::PrgRom => Ok(self.prg_rom[offset]),
+        }
+    }
+
+    /// ROMへの書き込みは無視し、エラーを返します。
+    pub fn write(&mut self, p: u16, data: u8) -> Result<(), EmuError> {
+        let (device, offset) = self.decode(p);
+        match device {
+            Device::Wram => self.wram[offset] = data,
+            Device::PpuRegister => self.ppu.write(ppu_register(offset), data)?,
+            Device::Apu => self.apu[offset] = data,
+            Device::Ram => self.ram[offset] = data,
+            Device::OpenBus => {}
+            Device::PrgRom => return Err(EmuError::WriteToRom { addr: p, data }),
+        }
+        Ok(())
+    }
+
+    /// 電源投入時の状態に戻します。
+    /// WRAMはbytesの値で埋め、I/Oレジスタは初期値に戻します。
+    pub fn power_on<I: Iterator<Item = u8>>(&mut self, bytes: &mut I) {
+        for (v, b) in self.wram.iter_mut().zip(bytes) {
+            *v = b;
+        }
+        self.ppu = IORegister::default();
+        self.scheduler.power_on();
+        self.apu = [0u8; APU_RANGE.end - APU_RANGE.start];
+    }
+
+    pub fn stack<'b>(&'b self, sp: u8) -> DisplayBinary<'b> {
+        DisplayBinary(&self.wram[(0x0100 + sp as usize)..0x0200])
     }
 
     pub fn wram<'b>(&'b self) -> DisplayBinary<'b> {
         DisplayBinary(&self.wram)
     }
 }
+
+/// PPUレジスタの領域内の位置をアドレスに変換する
+fn ppu_register(offset: usize) -> u16 {
+    (PPU_REGISTER_RANGE.start + offset) as u16
+}
+
+impl Bus for MemoryMap {
+    fn read(&mut self, addr: u16) -> Result<u8, EmuError> {
+        MemoryMap::read(self, addr)
+    }
+
+    fn write(&mut self, addr: u16, data: u8) -> Result<(), EmuError> {
+        MemoryMap::write(self, addr, data)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        MemoryMap::peek(self, addr)
+    }
+
+    /// PRG-ROMだけをキャッシュの対象にします。
+    fn bank(&self, addr: u16) -> Option<u32> {
+        match self.decode(addr) {
+            (Device::PrgRom, _) => Some(self.bank),
+            _ => None,
+        }
+    }
+
+    /// 1サイクル分PPUを進めます。
+    fn tick(&mut self) {
+        self.scheduler.tick(&mut self.ppu);
+    }
+
+    fn nmi(&self) -> bool {
+        self.ppu.nmi()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_wram_mirror() {
+        let mut m = MemoryMap::new(vec![]);
+        m.write(0x0800, 0x12).unwrap();
+        assert_eq!(m.read(0x0000), Ok(0x12));
+        m.write(0x07ff, 0x34).unwrap();
+        assert_eq!(m.read(0x1fff), Ok(0x34));
+    }
+
+    #[test]
+    fn it_ppu_register_mirror() {
+        let mut m = MemoryMap::new(vec![]);
+        m.write(0x2008, 0x80).unwrap();
+        assert_eq!(m.ppu.PPUCTRL.0, 0x80);
+        m.write(0x3ff9, 0x1e).unwrap();
+        assert_eq!(m.ppu.PPUMASK, 0x1e);
+        // 書き込み専用のレジスタはPPUのI/Oラッチの値が見える
+        assert_eq!(m.read(0x3ff8), Ok(0x1e));
+    }
+
+    #[test]
+    fn it_open_bus() {
+        let mut m = MemoryMap::new(vec![]);
+        m.write(0x5000, 0x12).unwrap();
+        assert_eq!(m.read(0x5000), Err(EmuError::OpenBus { addr: 0x5000 }));
+        assert_eq!(m.read(0x4000), Err(EmuError::OpenBus { addr: 0x4000 }));
+        m.write(0x4015, 0x0f).unwrap();
+        assert_eq!(m.read(0x4015), Ok(0x0f));
+    }
+
+    #[test]
+    fn it_prg_rom_mirror() {
+        let mut program = vec![0u8; 0x4000];
+        program[0] = 0x4c;
+        program[0x3fff] = 0xc0;
+        let mut m = MemoryMap::new(program);
+        assert_eq!(m.read(0xc000), Ok(0x4c));
+        assert_eq!(m.read(0xffff), Ok(0xc0));
+    }
+
+    #[test]
+    fn it_prg_rom_banks() {
+        // 16KBのバンク8つ(128KB)の先頭にバンク番号を書いておく
+        let mut program = vec![0u8; 0x20000];
+        for (bank, chunk) in program.chunks_mut(0x4000).enumerate() {
+            chunk[0] = bank as u8;
+        }
+        program[0x1fffc] = 0x34;
+        let mut m = MemoryMap::new(program);
+        assert_eq!(m.read(0x8000), Ok(0));
+        assert_eq!(m.read(0xc000), Ok(7));
+        assert_eq!(m.read(0xfffc), Ok(0x34));
+    }
+
+    #[test]
+    fn it_pages() {
+        let mut m = MemoryMap::new(vec![0u8; 0x4000]);
+        m.map(Region::new(0x6000, 0x60ff, 0x0080, Device::Ram));
+        m.map(Region::new(0x7f80, 0x7fff, 0x0100, Device::OpenBus));
+        // 表で引いても、領域を順に探しても同じ振り分け先になる
+        for addr in 0..=0xffff {
+            assert_eq!(m.decode(addr), search(&m.regions, addr));
+        }
+        // ページ内がすべて同じ領域なら表で引ける
+        assert!(m.pages[0x20].is_some());
+        assert!(m.pages[0x60].is_some());
+        assert!(m.pages[0x40].is_none());
+        assert!(m.pages[0x7f].is_none());
+    }
+
+    #[test]
+    fn it_map() {
+        let mut m = MemoryMap::new(vec![]);
+        // 0x6000～0x7FFFを1KBごとのミラーにする
+        m.map(Region::new(0x6000, 0x7fff, 0x0400, Device::Ram));
+        m.write(0x6001, 0x56).unwrap();
+        assert_eq!(m.read(0x7c01), Ok(0x56));
+        assert_eq!(m.decode(0x7c01), (Device::Ram, 0x0001));
+        assert_eq!(m.decode(0x8000), (Device::PrgRom, 0x0000));
+    }
+}
@@ -1,4 +1,16 @@
+pub mod bus;
 mod cpu;
-mod memory_map;
+pub mod decode_cache;
+pub mod execution_record;
+#[cfg(test)]
+pub(crate) mod fixture;
+pub mod history;
+pub mod interrupt;
+pub mod memory_map;
+pub mod power_on;
+pub mod ram;
 pub mod register;
 mod status_register;
+
+pub use bus::Bus;
+pub use cpu::Cpu;
@@ -0,0 +1,92 @@
+/// 電源投入時のWRAMとA、X、Yの内容
+/// 実機では不定なので、初期化していないメモリに頼ったバグを見つけるために切り替える。
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum PowerOnPolicy {
+    /// すべて$00
+    #[default]
+    Zero,
+    /// すべて$FF
+    Ff,
+    /// FCEUXと同じく、$00と$FFを4バイトずつ交互に並べる
+    Fceux,
+    /// シードから作った疑似乱数
+    Random(u64),
+}
+
+impl PowerOnPolicy {
+    /// 電源投入時の値を先頭から順に返します。
+    pub fn bytes(&self) -> PowerOnBytes {
+        PowerOnBytes {
+            policy: *self,
+            index: 0,
+            // xorshiftは状態が0のままになるので、シードをかき混ぜる
+            state: match self {
+                PowerOnPolicy::Random(seed) => seed ^ 0x9e37_79b9_7f4a_7c15,
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// PowerOnPolicy::bytesが返す、終わりのない値の並び
+pub struct PowerOnBytes {
+    policy: PowerOnPolicy,
+    index: usize,
+    state: u64,
+}
+
+impl Iterator for PowerOnBytes {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let v = match self.policy {
+            PowerOnPolicy::Zero => 0x00,
+            PowerOnPolicy::Ff => 0xff,
+            PowerOnPolicy::Fceux => {
+                if self.index & 0x04 == 0 {
+                    0x00
+                } else {
+                    0xff
+                }
+            }
+            PowerOnPolicy::Random(_) => {
+                // xorshift64*
+                self.state ^= self.state >> 12;
+                self.state ^= self.state << 25;
+                self.state ^= self.state >> 27;
+                (self.state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 56) as u8
+            }
+        };
+        self.index += 1;
+        Some(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first(policy: PowerOnPolicy) -> Vec<u8> {
+        policy.bytes().take(8).collect()
+    }
+
+    #[test]
+    fn it_power_on_policy() {
+        assert_eq!(first(PowerOnPolicy::Zero), vec![0x00; 8]);
+        assert_eq!(first(PowerOnPolicy::Ff), vec![0xff; 8]);
+        assert_eq!(
+            first(PowerOnPolicy::Fceux),
+            vec![0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff]
+        );
+        // 同じシードなら同じ値になる
+        assert_eq!(
+            first(PowerOnPolicy::Random(1)),
+            first(PowerOnPolicy::Random(1))
+        );
+        assert_ne!(
+            first(PowerOnPolicy::Random(1)),
+            first(PowerOnPolicy::Random(2))
+        );
+        assert_ne!(first(PowerOnPolicy::Random(0)), vec![0x00; 8]);
+    }
+}
@@ -0,0 +1,44 @@
+use super::bus::Bus;
+use crate::error::EmuError;
+
+const RAM_SIZE: usize = 0x10000;
+
+/// 64KiBすべてが読み書きできるRAMのバス
+/// I/OレジスタやROMのないCPU単体のテストに使う。
+#[derive(PartialEq, Eq, Debug)]
+pub struct Ram(Vec<u8>);
+
+impl Ram {
+    pub fn new() -> Self {
+        Ram(vec![0u8; RAM_SIZE])
+    }
+
+    /// addrからdataを書き込んだRAMを作成します。
+    pub fn with_data(addr: u16, data: &[u8]) -> Self {
+        let mut ram = Ram::new();
+        let addr = addr as usize;
+        ram.0[addr..addr + data.len()].copy_from_slice(data);
+        ram
+    }
+}
+
+impl Default for Ram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for Ram {
+    fn read(&mut self, addr: u16) -> Result<u8, EmuError> {
+        Ok(self.0[addr as usize])
+    }
+
+    fn write(&mut self, addr: u16, data: u8) -> Result<(), EmuError> {
+        self.0[addr as usize] = data;
+        Ok(())
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.0[addr as usize]
+    }
+}
@@ -12,9 +12,9 @@ pub struct Register {
     pub PC: u16,
     /// スタックポインタ
     /// スタックはWRAMの256が使える
-    /// 上位アドレスは0x01に固定
+    /// 上位アドレスは0x01に固定なので、下位8ビットだけを持つ
     /// 0x0100～0x01FF
-    pub SP: u16,
+    pub SP: u8,
     /// プロセッサステータスレジスタ
     pub P: StatusRegister,
 }
@@ -27,7 +27,8 @@ impl Register {
             X: 0x00,
             Y: 0x00,
             PC: 0x00,
-            SP: 0x01ff,
+            // 電源投入時は$00で、RESETで3つ減って$FDになる
+            SP: 0x00,
             P: StatusRegister::new(),
         }
     }
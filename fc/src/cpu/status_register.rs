@@ -87,6 +87,21 @@ impl StatusRegister {
     }
 }
 
+impl StatusRegister {
+    /// スタックへプッシュするときの値
+    /// B、Rフラグはレジスタとしては存在しないので、Rは常に1、
+    /// BはBRK、PHP命令のときだけ1としてプッシュします。
+    pub fn to_stack(self, b: bool) -> u8 {
+        let StatusRegister(v) = self.set_b(b);
+        v | R_FLAG
+    }
+    /// スタックからポップした値で復元します。
+    /// B、Rフラグはレジスタとしては存在しないので、Bは0、Rは1とします。
+    pub fn from_stack(v: u8) -> Self {
+        StatusRegister((v | R_FLAG) & !B_FLAG)
+    }
+}
+
 pub fn flag_n(v: u8) -> bool {
     v & N_FLAG == N_FLAG
 }
@@ -160,6 +175,23 @@ fn it_c() {
     assert_eq!(StatusRegister(0b0000_0000).c(), false);
 }
 
+#[test]
+fn it_to_stack() {
+    assert_eq!(StatusRegister(0b0000_0001).to_stack(true), 0b0011_0001);
+    assert_eq!(StatusRegister(0b0001_0001).to_stack(false), 0b0010_0001);
+}
+#[test]
+fn it_from_stack() {
+    assert_eq!(
+        StatusRegister::from_stack(0b0001_0001),
+        StatusRegister(0b0010_0001)
+    );
+    assert_eq!(
+        StatusRegister::from_stack(0b1100_0000),
+        StatusRegister(0b1110_0000)
+    );
+}
+
 // #[test]
 // fn it_flag_n() {
 //     assert_eq!(0.flag_n(0b1000_0000), N_FLAG);
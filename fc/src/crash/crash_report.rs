@@ -0,0 +1,188 @@
+use crate::binary::{self, DisplayBinary};
+use crate::cpu::execution_record::ExecutionRecord;
+use crate::cpu::{Bus, Cpu};
+use crate::error::Fault;
+use crate::ines::program::Command;
+use crate::ines::{INESHeader, INES};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// エミュレーションが止まった原因
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Cause {
+    /// FaultPolicy::BreakでCpu::stepが返したFault
+    Fault(Fault),
+    /// JAM命令でCPUが停止した
+    Jammed { addr: u16 },
+    /// エミュレータ内部のパニック
+    Panic(String),
+}
+
+impl std::fmt::Display for Cause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cause::Fault(fault) => write!(f, "{}", fault),
+            Cause::Jammed { addr } => write!(f, "CPU jammed at {:#06x}", addr),
+            Cause::Panic(message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+/// Cpu::stepを実行し、止まった場合はその原因を返します。
+/// パニックも捕まえるので、呼び出し元はCpuの状態をレポートに残せます。
+pub fn step<B: Bus>(cpu: &mut Cpu<B>) -> Result<&ExecutionRecord, Cause> {
+    // 記録を返せるように、cpuの参照ごとクロージャへ渡す
+    let result = panic::catch_unwind(AssertUnwindSafe(move || {
+        let cpu = cpu;
+        cpu.step()
+    }));
+    match result {
+        // JAMを実行したときだけCpuが停止する
+        Ok(Ok(record)) if record.cmd == Command::JAM => Err(Cause::Jammed { addr: record.addr }),
+        Ok(Ok(record)) => Ok(record),
+        Ok(Err(fault)) => Err(Cause::Fault(fault)),
+        Err(payload) => {
+            let message = if let Some(s) = payload.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = payload.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "unknown".to_string()
+            };
+            Err(Cause::Panic(message))
+        }
+    }
+}
+
+/// クラッシュレポートに載せるROMの情報
+/// CpuにはPRG-ROMの中身しか残らないので、INESをCpuに渡す前に作っておく。
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RomInfo {
+    pub header: INESHeader,
+    /// ヘッダを除いたPRG-ROMとCHR-ROMのCRC32
+    pub crc32: u32,
+}
+
+impl RomInfo {
+    pub fn new(ines: &INES) -> Self {
+        RomInfo {
+            header: ines.header.clone(),
+            crc32: binary::crc32(
+                &[&ines.program_rom_data[..], &ines.character_rom_data[..]].concat(),
+            ),
+        }
+    }
+}
+
+/// エミュレーションが止まったときのCPUとPPUの状態
+pub struct CrashReport<'a> {
+    pub cause: Cause,
+    pub rom: &'a RomInfo,
+    pub cpu: &'a Cpu,
+}
+
+impl<'a> CrashReport<'a> {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl<'a> std::fmt::Display for CrashReport<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cpu = self.cpu;
+        writeln!(f, "[Crash]{}", self.cause)?;
+        writeln!(
+            f,
+            "[ROM]\nCRC32: {:08X}\n{}",
+            self.rom.crc32, self.rom.header
+        )?;
+        writeln!(f, "[Register]\n{}", cpu.register)?;
+        writeln!(f, "[Cycles]{}", cpu.cycles)?;
+        writeln!(
+            f,
+            "[History] last {} instructions",
+            cpu.history.iter().count()
+        )?;
+        for record in cpu.history.iter() {
+            writeln!(f, "{}", record)?;
+        }
+        writeln!(f, "[PPURegister]\n{}", cpu.bus.ppu)?;
+        // SPより下も壊れたスタックの手がかりになるので、ページ全体を表示する
+        writeln!(
+            f,
+            "[Stack]\n{}",
+            DisplayBinary(&cpu.bus.wram[0x0100..0x0200])
+        )?;
+        writeln!(f, "[WRAM]\n{}", cpu.bus.wram())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::fixture::cpu_with_program;
+    use crate::error::EmuError;
+    use crate::ines;
+    use crate::io;
+
+    /// 読み込むとパニックするバス
+    struct PanicBus;
+
+    impl Bus for PanicBus {
+        fn read(&mut self, addr: u16) -> Result<u8, EmuError> {
+            panic!("read {:#06x}", addr)
+        }
+
+        fn write(&mut self, _addr: u16, _data: u8) -> Result<(), EmuError> {
+            Ok(())
+        }
+
+        fn peek(&self, _addr: u16) -> u8 {
+            0x00
+        }
+    }
+
+    #[test]
+    fn it_step() {
+        let mut cpu = cpu_with_program(&[
+            0xea, // NOP
+            0x02, // JAM
+        ]);
+        assert!(step(&mut cpu).is_ok());
+        assert_eq!(step(&mut cpu), Err(Cause::Jammed { addr: 0x8001 }));
+
+        let mut cpu = Cpu::with_bus(PanicBus);
+        assert_eq!(step(&mut cpu), Err(Cause::Panic("read 0x0000".to_string())));
+    }
+
+    #[test]
+    fn it_crash_report() {
+        let mut contents = io::read_to_binary("../docs/demo/sample1.nes").unwrap();
+        let ines = ines::parser(&mut contents).unwrap();
+        let rom = RomInfo::new(&ines);
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x42, // LDA #$42
+            0x48, // PHA
+            0x02, // JAM
+        ]);
+        cpu.register.SP = 0xfd;
+        let cause = loop {
+            if let Err(cause) = step(&mut cpu) {
+                break cause;
+            }
+        };
+        let report = CrashReport {
+            cause,
+            rom: &rom,
+            cpu: &cpu,
+        }
+        .to_string();
+
+        assert!(report.starts_with("[Crash]CPU jammed at 0x8003\n"));
+        assert!(report.contains(&format!("CRC32: {:08X}", rom.crc32)));
+        assert!(report.contains("[History] last 3 instructions"));
+        assert!(report.contains("[Write]0x01fd <= 0x42"));
+        assert!(report.contains("[PPURegister]"));
+        assert!(report.contains("[WRAM]"));
+    }
+}
@@ -0,0 +1,3 @@
+mod crash_report;
+
+pub use crash_report::{step, CrashReport, RomInfo};
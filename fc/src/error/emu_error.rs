@@ -0,0 +1,40 @@
+use crate::ines::program::Operand;
+
+/// エミュレーション中に起きた、実機ではエラーにならない異常
+/// 実機ではROMへの書き込みは無視され、何もつながっていないアドレスの読み込みはオープンバスの値になる。
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EmuError {
+    /// ROMへの書き込み
+    WriteToRom { addr: u16, data: u8 },
+    /// どの機器もデータバスを駆動しなかった読み込み(オープンバス)
+    /// CPUは最後にデータバスに乗った値を読み込む。実機で頼っているゲームもあるため、Faultにはしない。
+    OpenBus { addr: u16 },
+    /// 読み込み専用レジスタへの書き込み
+    WriteReadOnly { addr: u16, data: u8 },
+    /// アドレッシングモードに合わないオペランド
+    UnexpectedOperand(Operand),
+    /// JAM命令でCPUが停止した
+    Jammed { addr: u16 },
+}
+
+impl std::fmt::Display for EmuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmuError::WriteToRom { addr, data } => {
+                write!(f, "write {:#04x} to ROM at {:#06x}", data, addr)
+            }
+            EmuError::OpenBus { addr } => write!(f, "open bus read at {:#06x}", addr),
+            EmuError::WriteReadOnly { addr, data } => {
+                write!(
+                    f,
+                    "write {:#04x} to read-only register at {:#06x}",
+                    data, addr
+                )
+            }
+            EmuError::UnexpectedOperand(operand) => write!(f, "unexpected operand {}", operand),
+            EmuError::Jammed { addr } => write!(f, "CPU jammed at {:#06x}", addr),
+        }
+    }
+}
+
+impl std::error::Error for EmuError {}
@@ -0,0 +1,29 @@
+use super::EmuError;
+
+/// 命令の実行中に起きたEmuErrorと、その命令のアドレス
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Fault {
+    pub error: EmuError,
+    pub pc: u16,
+}
+
+impl std::fmt::Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (PC: {:#06x})", self.error, self.pc)
+    }
+}
+
+impl std::error::Error for Fault {}
+
+/// EmuErrorが起きたときの振る舞い
+/// どの場合も命令は実機と同じように最後まで実行する。
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum FaultPolicy {
+    /// 実機と同じく何もなかったものとして扱う
+    Ignore,
+    /// 標準エラー出力に表示して続ける
+    #[default]
+    Log,
+    /// 命令の実行後にCpu::stepがErrを返し、呼び出し元(デバッガ)に制御を戻す
+    Break,
+}
@@ -0,0 +1,5 @@
+mod emu_error;
+mod fault;
+
+pub use emu_error::EmuError;
+pub use fault::{Fault, FaultPolicy};
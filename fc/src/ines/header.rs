@@ -11,7 +11,7 @@ use std::io::{Cursor, Error, Read, Result};
 /// 9: Flags 9 - TV system (rarely used extension)
 /// 10: Flags 10 - TV system, PRG-RAM presence (unofficial, rarely used extension)
 /// 11-15: Unused padding (should be filled with zero, but some rippers put their name across bytes 7-15)
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct INESHeader {
     pub nes: [u8; 4],
     pub program_rom_size: u8,
@@ -48,6 +48,31 @@ impl INESHeader {
     }
 }
 
+impl std::fmt::Display for INESHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // マッパー番号の下位4ビットはFlags 6、上位4ビットはFlags 7にある
+        let mapper = (self.flag7 & 0xf0) | (self.flag6 >> 4);
+        let mirroring = if self.flag6 & 0x01 == 0 {
+            "horizontal"
+        } else {
+            "vertical"
+        };
+        write!(
+            f,
+            "PRG-ROM: {} x 16KB, CHR-ROM: {} x 8KB, Mapper: {}, Mirroring: {}\nFlags6: {:#04x}, Flags7: {:#04x}, Flags8: {:#04x}, Flags9: {:#04x}, Flags10: {:#04x}",
+            self.program_rom_size,
+            self.character_rom_size,
+            mapper,
+            mirroring,
+            self.flag6,
+            self.flag7,
+            self.flag8,
+            self.flag9,
+            self.flag10,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,4 +3,5 @@ mod ines;
 pub mod program;
 pub mod sprite;
 
+pub use header::INESHeader;
 pub use ines::{parser, INES};
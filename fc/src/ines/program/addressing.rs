@@ -68,13 +68,9 @@ impl Addresing for &[u8] {
     /// 次の命令を示すプログラムカウンタに2番目のバイトを加算（符号拡張）した値を実効アドレスとします。
     /// オフセットとして、-128（$80）～+127（$7F）を指定できます。
     fn relative(&self, addr: usize) -> Operand {
-        let v = self[addr + 1] as u16;
+        let v = self[addr + 1] as i8;
         let next_program = addr as u16 + 2;
-        Operand::Addr(if v >= 0b1000_0000 {
-            0x8000 + next_program + v - 256
-        } else {
-            0x8000 + next_program + v
-        })
+        Operand::Addr(next_program.wrapping_add(v as u16))
     }
 
     /// 上位アドレスを$00とし、
@@ -109,6 +105,20 @@ impl Addresing for &[u8] {
     }
 }
 
+/// 上位アドレスとして$00、下位アドレスとしてbaseにインデックスレジスタを加算（8）した値
+/// キャリーは無視するので、$FFを超えるとゼロページの先頭に戻ります。
+pub fn zero_page_indexed(base: u8, index: u8) -> u16 {
+    binary::u8u8_to_u16(0x00, base.wrapping_add(index))
+}
+
+/// 間接参照で実効アドレスの上位バイトを読み込むアドレス
+/// 6502は下位バイトのアドレスをインクリメントするときに上位アドレスへ繰り上げません。
+/// そのため$10FFの次は$1000、ゼロページの$FFの次は$00になります。
+pub fn next_in_page(addr: u16) -> u16 {
+    let (upper, lower) = binary::u16_to_u8u8(addr);
+    binary::u8u8_to_u16(upper, lower.wrapping_add(1))
+}
+
 #[test]
 fn ts_accumulator() {
     let raw = &(vec![0, 1, 2, 3, 4, 5][..]);
@@ -180,3 +190,16 @@ fn ts_none() {
     let raw = &(vec![0, 1, 2, 3, 4, 5][..]);
     assert_eq!(raw.none(0), Operand::None);
 }
+
+#[test]
+fn ts_zero_page_indexed() {
+    assert_eq!(zero_page_indexed(0x10, 0x01), 0x0011);
+    assert_eq!(zero_page_indexed(0xff, 0x01), 0x0000);
+    assert_eq!(zero_page_indexed(0x80, 0xff), 0x007f);
+}
+#[test]
+fn ts_next_in_page() {
+    assert_eq!(next_in_page(0x1000), 0x1001);
+    assert_eq!(next_in_page(0x10ff), 0x1000);
+    assert_eq!(next_in_page(0x00ff), 0x0000);
+}
@@ -56,4 +56,44 @@ pub enum Command {
     PHP,
     PLP,
     NOP,
+    // 以下は非公式命令
+    // reference: https://www.nesdev.org/wiki/Programming_with_unofficial_opcodes
+    /// ASL + ORA
+    SLO,
+    /// ROL + AND
+    RLA,
+    /// LSR + EOR
+    SRE,
+    /// ROR + ADC
+    RRA,
+    /// A & X -> M
+    SAX,
+    /// LDA + LDX
+    LAX,
+    /// DEC + CMP
+    DCP,
+    /// INC + SBC
+    ISC,
+    /// AND #i, N -> C
+    ANC,
+    /// AND #i + LSR A
+    ALR,
+    /// AND #i + ROR A
+    ARR,
+    /// (A & X) - #i -> X
+    AXS,
+    /// 不安定な命令 A & X & #i -> A
+    XAA,
+    /// 不安定な命令 A & X & (H + 1) -> M
+    AHX,
+    /// 不安定な命令 A & X -> SP, A & X & (H + 1) -> M
+    TAS,
+    /// 不安定な命令 Y & (H + 1) -> M
+    SHY,
+    /// 不安定な命令 X & (H + 1) -> M
+    SHX,
+    /// M & SP -> A, X, SP
+    LAS,
+    /// CPUを停止させる(KIL)
+    JAM,
 }
@@ -6,6 +6,7 @@ mod order_set;
 mod program;
 mod programs;
 
+pub use addressing::{next_in_page, zero_page_indexed};
 pub use command::Command;
 pub use mode::Mode;
 pub use operand::Operand;
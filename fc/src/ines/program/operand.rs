@@ -1,4 +1,5 @@
 use crate::binary;
+use crate::error::EmuError;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Operand {
@@ -8,20 +9,16 @@ pub enum Operand {
 }
 
 impl Operand {
-    pub fn addr(&self) -> u16 {
+    pub fn addr(&self) -> Result<u16, EmuError> {
         if let Operand::Addr(addr) = self {
-            addr.clone()
+            Ok(*addr)
         } else {
-            panic!("unexpected");
+            Err(EmuError::UnexpectedOperand(*self))
         }
     }
 
-    pub fn addrs(&self) -> (u8, u8) {
-        if let Operand::Addr(addr) = self {
-            binary::u16_to_u8u8(addr.clone())
-        } else {
-            panic!("unexpected");
-        }
+    pub fn addrs(&self) -> Result<(u8, u8), EmuError> {
+        self.addr().map(binary::u16_to_u8u8)
     }
 }
 
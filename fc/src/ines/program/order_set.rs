@@ -7,6 +7,8 @@ pub struct OrderSet {
     pub cmd: Command,
     pub length: usize,
     pub clock: usize,
+    /// 公式のドキュメントに載っている命令かどうか
+    pub official: bool,
 }
 
 impl OrderSet {
@@ -16,6 +18,15 @@ impl OrderSet {
             cmd,
             length,
             clock,
+            official: true,
+        }
+    }
+
+    /// 非公式(undocumented)命令
+    pub fn unofficial(mode: Mode, cmd: Command, length: usize, clock: usize) -> Self {
+        OrderSet {
+            official: false,
+            ..OrderSet::new(mode, cmd, length, clock)
         }
     }
     pub fn none() -> Self {
@@ -33,286 +44,359 @@ impl std::fmt::Display for OrderSet {
 /// # - denotes an immediate value
 /// #$ - denotes an immediate hex value
 /// #% - denotes an immediate binary value
+/// * - denotes an unofficial opcode
+///
+/// reference: https://www.nesdev.org/wiki/CPU_unofficial_opcodes
 pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
     [
         // 0x00
         // Implied       BRK           $00   1   7
         // Indirect,X    ORA ($44,X)   $01   2   6
+        // Implied       *JAM          $02   1   2
+        // Indirect,X    *SLO ($44,X)  $03   2   8
+        // Zero Page     *NOP $44      $04   2   3
         // Zero Page     ORA $44       $05   2   3
         // Zero Page     ASL $44       $06   2   5
+        // Zero Page     *SLO $44      $07   2   5
         // Implied       PHP           $08   1   3
         // Immediate     ORA #$44      $09   2   2
         // Accumulator   ASL A         $0A   1   2
+        // Immediate     *ANC #$44     $0B   2   2
+        // Absolute      *NOP $4400    $0C   3   4
         // Absolute      ORA $4400     $0D   3   4
         // Absolute      ASL $4400     $0E   3   6
+        // Absolute      *SLO $4400    $0F   3   6
         OrderSet::new(Mode::Implied, Command::BRK, 1, 7),
         OrderSet::new(Mode::IndirectX, Command::ORA, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::SLO, 2, 8),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::NOP, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::ORA, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::ASL, 2, 5),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::SLO, 2, 5),
         //
         OrderSet::new(Mode::Implied, Command::PHP, 1, 3),
         OrderSet::new(Mode::Immediate, Command::ORA, 2, 2),
         OrderSet::new(Mode::Accumulator, Command::ASL, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::ANC, 2, 2),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::NOP, 3, 4),
         OrderSet::new(Mode::Absolute, Command::ORA, 3, 4),
         OrderSet::new(Mode::Absolute, Command::ASL, 3, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::SLO, 3, 6),
         // 0x10
         // Relative      BPL $44       $10   2   2 +1or2
         // Indirect,Y    ORA ($44),Y   $11   2   5 +1
+        // Implied       *JAM          $12   1   2
+        // Indirect,Y    *SLO ($44),Y  $13   2   8
+        // Zero Page,X   *NOP $44,X    $14   2   4
         // Zero Page,X   ORA $44,X     $15   2   4
         // Zero Page,X   ASL $44,X     $16   2   6
+        // Zero Page,X   *SLO $44,X    $17   2   6
         // Implied       CLC           $18   1   2
         // Absolute,Y    ORA $4400,Y   $19   3   4 +1
+        // Implied       *NOP          $1A   1   2
+        // Absolute,Y    *SLO $4400,Y  $1B   3   7
+        // Absolute,X    *NOP $4400,X  $1C   3   4 +1
         // Absolute,X    ORA $4400,X   $1D   3   4 +1
-        // Absolute,X    ASL $4400,X   $1E   3   6 +1
+        // Absolute,X    ASL $4400,X   $1E   3   7
+        // Absolute,X    *SLO $4400,X  $1F   3   7
         OrderSet::new(Mode::Relative, Command::BPL, 2, 2),
         OrderSet::new(Mode::IndirectY, Command::ORA, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::SLO, 2, 8),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::NOP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::ORA, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::ASL, 2, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::SLO, 2, 6),
         //
         OrderSet::new(Mode::Implied, Command::CLC, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::ORA, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::NOP, 1, 2),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::SLO, 3, 7),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::NOP, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::ORA, 3, 4),
-        OrderSet::new(Mode::AbsoluteX, Command::ASL, 3, 6),
-        OrderSet::none(),
+        OrderSet::new(Mode::AbsoluteX, Command::ASL, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::SLO, 3, 7),
         // 0x20
         // Absolute      JSR $5597     $20   3   6
         // Indirect,X    AND ($44,X)   $21   2   6
+        // Implied       *JAM          $22   1   2
+        // Indirect,X    *RLA ($44,X)  $23   2   8
         // Zero Page     BIT $44       $24   2   3
         // Zero Page     AND $44       $25   2   3
         // Zero Page     ROL $44       $26   2   5
+        // Zero Page     *RLA $44      $27   2   5
         // Implied       PLP           $28   1   4
         // Immediate     AND #$44      $29   2   2
         // Accumulator   ROL A         $2A   1   2
+        // Immediate     *ANC #$44     $2B   2   2
         // Absolute      BIT $4400     $2C   3   4
         // Absolute      AND $4400     $2D   3   4
         // Absolute      ROL $4400     $2E   3   6
+        // Absolute      *RLA $4400    $2F   3   6
         OrderSet::new(Mode::Absolute, Command::JSR, 3, 6),
         OrderSet::new(Mode::IndirectX, Command::AND, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::RLA, 2, 8),
         //
         OrderSet::new(Mode::ZeroPage, Command::BIT, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::AND, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::ROL, 2, 5),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::RLA, 2, 5),
         //
         OrderSet::new(Mode::Implied, Command::PLP, 1, 4),
         OrderSet::new(Mode::Immediate, Command::AND, 2, 2),
         OrderSet::new(Mode::Accumulator, Command::ROL, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::ANC, 2, 2),
         //
         OrderSet::new(Mode::Absolute, Command::BIT, 3, 4),
         OrderSet::new(Mode::Absolute, Command::AND, 3, 4),
         OrderSet::new(Mode::Absolute, Command::ROL, 3, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::RLA, 3, 6),
         // 0x30
         // Relative      BMI $44       $30   2   2 +1or2
         // Indirect,Y    AND ($44),Y   $31   2   5 +1
+        // Implied       *JAM          $32   1   2
+        // Indirect,Y    *RLA ($44),Y  $33   2   8
+        // Zero Page,X   *NOP $44,X    $34   2   4
         // Zero Page,X   AND $44,X     $35   2   4
         // Zero Page,X   ROL $44,X     $36   2   6
+        // Zero Page,X   *RLA $44,X    $37   2   6
         // Implied       SEC           $38   1   2
         // Absolute,Y    AND $4400,Y   $39   3   4 +1
+        // Implied       *NOP          $3A   1   2
+        // Absolute,Y    *RLA $4400,Y  $3B   3   7
+        // Absolute,X    *NOP $4400,X  $3C   3   4 +1
         // Absolute,X    AND $4400,X   $3D   3   4 +1
-        // Absolute,X    ROL $4400,X   $3E   3   6 +1
+        // Absolute,X    ROL $4400,X   $3E   3   7
+        // Absolute,X    *RLA $4400,X  $3F   3   7
         OrderSet::new(Mode::Relative, Command::BMI, 2, 2),
-        OrderSet::new(Mode::IndirectX, Command::AND, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::new(Mode::IndirectY, Command::AND, 2, 5),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::RLA, 2, 8),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::NOP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::AND, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::ROL, 2, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::RLA, 2, 6),
         //
         OrderSet::new(Mode::Implied, Command::SEC, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::AND, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::NOP, 1, 2),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::RLA, 3, 7),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::NOP, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::AND, 3, 4),
-        OrderSet::new(Mode::AbsoluteX, Command::ROL, 3, 6),
-        OrderSet::none(),
+        OrderSet::new(Mode::AbsoluteX, Command::ROL, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::RLA, 3, 7),
         // 0x40
         // Implied       RTI           $40   1   6
         // Indirect,X    EOR ($44,X)   $41   2   6
+        // Implied       *JAM          $42   1   2
+        // Indirect,X    *SRE ($44,X)  $43   2   8
+        // Zero Page     *NOP $44      $44   2   3
         // Zero Page     EOR $44       $45   2   3
         // Zero Page     LSR $44       $46   2   5
+        // Zero Page     *SRE $44      $47   2   5
         // Implied       PHA           $48   1   3
         // Immediate     EOR #$44      $49   2   2
         // Accumulator   LSR A         $4A   1   2
+        // Immediate     *ALR #$44     $4B   2   2
         // Absolute      JMP $5597     $4C   3   3
         // Absolute      EOR $4400     $4D   3   4
         // Absolute      LSR $4400     $4E   3   6
+        // Absolute      *SRE $4400    $4F   3   6
         OrderSet::new(Mode::Implied, Command::RTI, 1, 6),
         OrderSet::new(Mode::IndirectX, Command::EOR, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::SRE, 2, 8),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::NOP, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::EOR, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::LSR, 2, 5),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::SRE, 2, 5),
         //
         OrderSet::new(Mode::Implied, Command::PHA, 1, 3),
         OrderSet::new(Mode::Immediate, Command::EOR, 2, 2),
         OrderSet::new(Mode::Accumulator, Command::LSR, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::ALR, 2, 2),
         //
         OrderSet::new(Mode::Absolute, Command::JMP, 3, 3),
         OrderSet::new(Mode::Absolute, Command::EOR, 3, 4),
         OrderSet::new(Mode::Absolute, Command::LSR, 3, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::SRE, 3, 6),
         // 0x50
         // Relative      BVC $44       $50   2   2 +1or2
         // Indirect,Y    EOR ($44),Y   $51   2   5 +1
+        // Implied       *JAM          $52   1   2
+        // Indirect,Y    *SRE ($44),Y  $53   2   8
+        // Zero Page,X   *NOP $44,X    $54   2   4
         // Zero Page,X   EOR $44,X     $55   2   4
         // Zero Page,X   LSR $44,X     $56   2   6
+        // Zero Page,X   *SRE $44,X    $57   2   6
         // Implied       CLI           $58   1   2
         // Absolute,Y    EOR $4400,Y   $59   3   4 +1
+        // Implied       *NOP          $5A   1   2
+        // Absolute,Y    *SRE $4400,Y  $5B   3   7
+        // Absolute,X    *NOP $4400,X  $5C   3   4 +1
         // Absolute,X    EOR $4400,X   $5D   3   4 +1
-        // Absolute,X    LSR $4400,X   $5E   3   6 +1
+        // Absolute,X    LSR $4400,X   $5E   3   7
+        // Absolute,X    *SRE $4400,X  $5F   3   7
         OrderSet::new(Mode::Relative, Command::BVC, 2, 2),
         OrderSet::new(Mode::IndirectY, Command::EOR, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::SRE, 2, 8),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::NOP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::EOR, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::LSR, 2, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::SRE, 2, 6),
         OrderSet::new(Mode::Implied, Command::CLI, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::EOR, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::NOP, 1, 2),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::SRE, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::NOP, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::EOR, 3, 4),
-        OrderSet::new(Mode::AbsoluteX, Command::LSR, 3, 6),
-        OrderSet::none(),
+        OrderSet::new(Mode::AbsoluteX, Command::LSR, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::SRE, 3, 7),
         // 0x60
         // Implied       RTS           $60   1   6
         // Indirect,X    ADC ($44,X)   $61   2   6
+        // Implied       *JAM          $62   1   2
+        // Indirect,X    *RRA ($44,X)  $63   2   8
+        // Zero Page     *NOP $44      $64   2   3
         // Zero Page     ADC $44       $65   2   3
         // Zero Page     ROR $44       $66   2   5
+        // Zero Page     *RRA $44      $67   2   5
         // Implied       PLA           $68   1   4
         // Immediate     ADC #$44      $69   2   2
         // Accumulator   ROR A         $6A   1   2
+        // Immediate     *ARR #$44     $6B   2   2
         // Indirect      JMP ($5597)   $6C   3   5
         // Absolute      ADC $4400     $6D   3   4
         // Absolute      ROR $4400     $6E   3   6
+        // Absolute      *RRA $4400    $6F   3   6
         OrderSet::new(Mode::Implied, Command::RTS, 1, 6),
         OrderSet::new(Mode::IndirectX, Command::ADC, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::RRA, 2, 8),
+        OrderSet::unofficial(Mode::ZeroPage, Command::NOP, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::ADC, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::ROR, 2, 5),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::RRA, 2, 5),
         OrderSet::new(Mode::Implied, Command::PLA, 1, 4),
         OrderSet::new(Mode::Immediate, Command::ADC, 2, 2),
         OrderSet::new(Mode::Accumulator, Command::ROR, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::ARR, 2, 2),
         OrderSet::new(Mode::Indirect, Command::JMP, 3, 5),
         OrderSet::new(Mode::Absolute, Command::ADC, 3, 4),
         OrderSet::new(Mode::Absolute, Command::ROR, 3, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::RRA, 3, 6),
         // 0x70
         // Relative      BVS $44       $70   2   2 +1or2
         // Indirect,Y    ADC ($44),Y   $71   2   5 +1
+        // Implied       *JAM          $72   1   2
+        // Indirect,Y    *RRA ($44),Y  $73   2   8
+        // Zero Page,X   *NOP $44,X    $74   2   4
         // Zero Page,X   ADC $44,X     $75   2   4
         // Zero Page,X   ROR $44,X     $76   2   6
+        // Zero Page,X   *RRA $44,X    $77   2   6
         // Implied       SEI           $78   1   2
         // Absolute,Y    ADC $4400,Y   $79   3   4 +1
+        // Implied       *NOP          $7A   1   2
+        // Absolute,Y    *RRA $4400,Y  $7B   3   7
+        // Absolute,X    *NOP $4400,X  $7C   3   4 +1
         // Absolute,X    ADC $4400,X   $7D   3   4 +1
-        // Absolute,X    ROR $4400,X   $7E   3   6 +1
+        // Absolute,X    ROR $4400,X   $7E   3   7
+        // Absolute,X    *RRA $4400,X  $7F   3   7
         OrderSet::new(Mode::Relative, Command::BVS, 2, 2),
         OrderSet::new(Mode::IndirectY, Command::ADC, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::RRA, 2, 8),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::NOP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::ADC, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::ROR, 2, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::RRA, 2, 6),
         OrderSet::new(Mode::Implied, Command::SEI, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::ADC, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::NOP, 1, 2),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::RRA, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::NOP, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::ADC, 3, 4),
-        OrderSet::new(Mode::AbsoluteX, Command::ROR, 3, 6),
-        OrderSet::none(),
+        OrderSet::new(Mode::AbsoluteX, Command::ROR, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::RRA, 3, 7),
         // 0x80
+        // Immediate     *NOP #$44     $80   2   2
         // Indirect,X    STA ($44,X)   $81   2   6
+        // Immediate     *NOP #$44     $82   2   2
+        // Indirect,X    *SAX ($44,X)  $83   2   6
         // Zero Page     STY $44       $84   2   3
         // Zero Page     STA $44       $85   2   3
         // Zero Page     STX $44       $86   2   3
+        // Zero Page     *SAX $44      $87   2   3
         // Implied       DEY           $88   1   2
+        // Immediate     *NOP #$44     $89   2   2
         // Implied       TXA           $8A   1   2
+        // Immediate     *XAA #$44     $8B   2   2
         // Absolute      STY $4400     $8C   3   4
         // Absolute      STA $4400     $8D   3   4
         // Absolute      STX $4400     $8E   3   4
-        OrderSet::none(),
+        // Absolute      *SAX $4400    $8F   3   4
+        OrderSet::unofficial(Mode::Immediate, Command::NOP, 2, 2),
         OrderSet::new(Mode::IndirectX, Command::STA, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::NOP, 2, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::SAX, 2, 6),
         OrderSet::new(Mode::ZeroPage, Command::STY, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::STA, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::STX, 2, 3),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::SAX, 2, 3),
         OrderSet::new(Mode::Implied, Command::DEY, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::NOP, 2, 2),
         OrderSet::new(Mode::Implied, Command::TXA, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::XAA, 2, 2),
         OrderSet::new(Mode::Absolute, Command::STY, 3, 4),
         OrderSet::new(Mode::Absolute, Command::STA, 3, 4),
         OrderSet::new(Mode::Absolute, Command::STX, 3, 4),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::SAX, 3, 4),
         // 0x90
         // Relative      BCC $44       $90   2   2 +1or2
-        // Indirect,Y    STA ($44),Y   $91   2   5 +1
+        // Indirect,Y    STA ($44),Y   $91   2   6
+        // Implied       *JAM          $92   1   2
+        // Indirect,Y    *AHX ($44),Y  $93   2   6
         // Zero Page,X   STY $44,X     $94   2   4
         // Zero Page,X   STA $44,X     $95   2   4
         // Zero Page,Y   STX $44,Y     $96   2   4
+        // Zero Page,Y   *SAX $44,Y    $97   2   4
         // Implied       TYA           $98   1   2
-        // Absolute,Y    STA $4400,Y   $99   3   4 +1
+        // Absolute,Y    STA $4400,Y   $99   3   5
         // Implied       TXS           $9A   1   2
-        // Absolute,X    STA $4400,X   $9D   3   4 +1
+        // Absolute,Y    *TAS $4400,Y  $9B   3   5
+        // Absolute,X    *SHY $4400,X  $9C   3   5
+        // Absolute,X    STA $4400,X   $9D   3   5
+        // Absolute,Y    *SHX $4400,Y  $9E   3   5
+        // Absolute,Y    *AHX $4400,Y  $9F   3   5
         OrderSet::new(Mode::Relative, Command::BCC, 2, 2),
-        OrderSet::new(Mode::IndirectY, Command::STA, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::new(Mode::IndirectY, Command::STA, 2, 6),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::AHX, 2, 6),
         //
         OrderSet::new(Mode::ZeroPageX, Command::STY, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::STA, 2, 4),
         OrderSet::new(Mode::ZeroPageY, Command::STX, 2, 4),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageY, Command::SAX, 2, 4),
         //
         OrderSet::new(Mode::Implied, Command::TYA, 1, 2),
-        OrderSet::new(Mode::AbsoluteY, Command::STA, 3, 4),
+        OrderSet::new(Mode::AbsoluteY, Command::STA, 3, 5),
         OrderSet::new(Mode::Implied, Command::TXS, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::TAS, 3, 5),
         //
-        OrderSet::none(),
-        OrderSet::new(Mode::AbsoluteX, Command::STA, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::SHY, 3, 5),
+        OrderSet::new(Mode::AbsoluteX, Command::STA, 3, 5),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::SHX, 3, 5),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::AHX, 3, 5),
         // 0xa0
         // Immediate     LDY #$44      $A0   2   2
         // Indirect,X    LDA ($44,X)   $A1   2   6
@@ -329,22 +413,22 @@ pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
         OrderSet::new(Mode::Immediate, Command::LDY, 2, 2),
         OrderSet::new(Mode::IndirectX, Command::LDA, 2, 6),
         OrderSet::new(Mode::Immediate, Command::LDX, 2, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::IndirectX, Command::LAX, 2, 6),
         //
         OrderSet::new(Mode::ZeroPage, Command::LDY, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::LDA, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::LDX, 2, 3),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::LAX, 2, 3),
         //
         OrderSet::new(Mode::Implied, Command::TAY, 1, 2),
         OrderSet::new(Mode::Immediate, Command::LDA, 2, 2),
         OrderSet::new(Mode::Implied, Command::TAX, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::LAX, 2, 2),
         //
         OrderSet::new(Mode::Absolute, Command::LDY, 3, 4),
         OrderSet::new(Mode::Absolute, Command::LDA, 3, 4),
         OrderSet::new(Mode::Absolute, Command::LDX, 3, 4),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::LAX, 3, 4),
         // 0xb0
         // Relative      BCS $44       $B0   2   2 +1or2
         // Indirect,Y    LDA ($44),Y   $B1   2   5 +1
@@ -358,24 +442,24 @@ pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
         // Absolute,X    LDA $4400,X   $BD   3   4 +1
         // Absolute,Y    LDX $4400,Y   $BE   3   4 +1
         OrderSet::new(Mode::Relative, Command::BCS, 2, 2),
-        OrderSet::new(Mode::IndirectX, Command::LDA, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::new(Mode::IndirectY, Command::LDA, 2, 5),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::LAX, 2, 5),
         //
         OrderSet::new(Mode::ZeroPageX, Command::LDY, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::LDA, 2, 4),
         OrderSet::new(Mode::ZeroPageY, Command::LDX, 2, 4),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageY, Command::LAX, 2, 4),
         //
         OrderSet::new(Mode::Implied, Command::CLV, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::LDA, 3, 4),
         OrderSet::new(Mode::Implied, Command::TSX, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::LAS, 3, 4),
         //
         OrderSet::new(Mode::AbsoluteX, Command::LDY, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::LDA, 3, 4),
         OrderSet::new(Mode::AbsoluteY, Command::LDX, 3, 4),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::LAX, 3, 4),
         // 0xc0
         // Immediate     CPY #$44      $C0   2   2
         // Indirect,X    CMP ($44,X)   $C1   2   6
@@ -390,23 +474,23 @@ pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
         // Absolute      DEC $4400     $CE   3   6
         OrderSet::new(Mode::Immediate, Command::CPY, 2, 2),
         OrderSet::new(Mode::IndirectX, Command::CMP, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::NOP, 2, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::DCP, 2, 8),
         //
         OrderSet::new(Mode::ZeroPage, Command::CPY, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::CMP, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::DEC, 2, 5),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::DCP, 2, 5),
         //
         OrderSet::new(Mode::Implied, Command::INY, 1, 2),
         OrderSet::new(Mode::Immediate, Command::CMP, 2, 2),
         OrderSet::new(Mode::Implied, Command::DEX, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::AXS, 2, 2),
         //
         OrderSet::new(Mode::Absolute, Command::CPY, 3, 4),
         OrderSet::new(Mode::Absolute, Command::CMP, 3, 4),
         OrderSet::new(Mode::Absolute, Command::DEC, 3, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::DCP, 3, 6),
         // 0xd0
         // Relative      BNE $44       $D0   2   2 +1or2
         // Indirect,Y    CMP ($44),Y   $D1   2   5 +1
@@ -415,26 +499,26 @@ pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
         // Implied       CLD           $D8   1   2
         // Absolute,Y    CMP $4400,Y   $D9   3   4 +1
         // Absolute,X    CMP $4400,X   $DD   3   4 +1
-        // Absolute,X    DEC $4400,X   $DE   3   6 +1
+        // Absolute,X    DEC $4400,X   $DE   3   7
         OrderSet::new(Mode::Relative, Command::BNE, 2, 2),
         OrderSet::new(Mode::IndirectY, Command::CMP, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::DCP, 2, 8),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::NOP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::CMP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::DEC, 2, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::DCP, 2, 6),
         //
         OrderSet::new(Mode::Implied, Command::CLD, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::CMP, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::NOP, 1, 2),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::DCP, 3, 7),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::NOP, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::CMP, 3, 4),
-        OrderSet::new(Mode::AbsoluteX, Command::DEC, 3, 6),
-        OrderSet::none(),
+        OrderSet::new(Mode::AbsoluteX, Command::DEC, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::DCP, 3, 7),
         // 0xe0
         // Immediate     CPX #$44      $E0   2   2
         // Indirect,X    SBC ($44,X)   $E1   2   6
@@ -449,23 +533,23 @@ pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
         // Absolute      INC $4400     $EE   3   6
         OrderSet::new(Mode::Immediate, Command::CPX, 2, 2),
         OrderSet::new(Mode::IndirectX, Command::SBC, 2, 6),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::NOP, 2, 2),
+        OrderSet::unofficial(Mode::IndirectX, Command::ISC, 2, 8),
         //
         OrderSet::new(Mode::ZeroPage, Command::CPX, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::SBC, 2, 3),
         OrderSet::new(Mode::ZeroPage, Command::INC, 2, 5),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPage, Command::ISC, 2, 5),
         //
         OrderSet::new(Mode::Implied, Command::INX, 1, 2),
         OrderSet::new(Mode::Immediate, Command::SBC, 2, 2),
         OrderSet::new(Mode::Implied, Command::NOP, 1, 2),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Immediate, Command::SBC, 2, 2),
         //
         OrderSet::new(Mode::Absolute, Command::CPX, 3, 4),
         OrderSet::new(Mode::Absolute, Command::SBC, 3, 4),
         OrderSet::new(Mode::Absolute, Command::INC, 3, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Absolute, Command::ISC, 3, 6),
         // 0xf0
         // Relative      BEQ $44       $F0   2   2 +1or2
         // Indirect,Y    SBC ($44),Y   $F1   2   5 +1
@@ -474,25 +558,25 @@ pub static OrderSets: Lazy<[OrderSet; 16 * 16]> = Lazy::new(|| {
         // Implied       SED           $F8   1   2
         // Absolute,Y    SBC $4400,Y   $F9   3   4 +1
         // Absolute,X    SBC $4400,X   $FD   3   4 +1
-        // Absolute,X    INC $4400,X   $FE   3   6 +1
+        // Absolute,X    INC $4400,X   $FE   3   7
         OrderSet::new(Mode::Relative, Command::BEQ, 2, 2),
         OrderSet::new(Mode::IndirectY, Command::SBC, 2, 5),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::JAM, 1, 2),
+        OrderSet::unofficial(Mode::IndirectY, Command::ISC, 2, 8),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::NOP, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::SBC, 2, 4),
         OrderSet::new(Mode::ZeroPageX, Command::INC, 2, 6),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::ZeroPageX, Command::ISC, 2, 6),
         //
         OrderSet::new(Mode::Implied, Command::SED, 1, 2),
         OrderSet::new(Mode::AbsoluteY, Command::SBC, 3, 4),
-        OrderSet::none(),
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::Implied, Command::NOP, 1, 2),
+        OrderSet::unofficial(Mode::AbsoluteY, Command::ISC, 3, 7),
         //
-        OrderSet::none(),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::NOP, 3, 4),
         OrderSet::new(Mode::AbsoluteX, Command::SBC, 3, 4),
-        OrderSet::new(Mode::AbsoluteX, Command::INC, 3, 6),
-        OrderSet::none(),
+        OrderSet::new(Mode::AbsoluteX, Command::INC, 3, 7),
+        OrderSet::unofficial(Mode::AbsoluteX, Command::ISC, 3, 7),
     ]
 });
@@ -10,8 +10,13 @@ pub struct Program {
     pub operand: Operand,
     pub addr: u16,
 }
+/// {:#}で表示すると、アセンブリの書式で表示します。
+/// 例: LDA #$44、LDA $0200,X、JMP ($0200)
 impl std::fmt::Display for Program {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return self.fmt_asm(f);
+        }
         write!(
             f,
             "@{:#06x} #{:#04x} => {} {}",
@@ -21,6 +26,31 @@ impl std::fmt::Display for Program {
 }
 
 impl Program {
+    /// アセンブリの書式で表示します。
+    /// オペランドの種類がアドレッシングモードと食い違っていてもpanicせず、その値をそのまま表示します。
+    fn fmt_asm(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.orderset.cmd;
+        let v = match self.operand {
+            Operand::Data(v) => v as u16,
+            Operand::Addr(addr) => addr,
+            Operand::None => 0,
+        };
+        match self.orderset.mode {
+            Mode::Implied | Mode::NONE => write!(f, "{:?}", name),
+            Mode::Accumulator => write!(f, "{:?} A", name),
+            Mode::Immediate => write!(f, "{:?} #${:02X}", name, v),
+            Mode::ZeroPage => write!(f, "{:?} ${:02X}", name, v),
+            Mode::ZeroPageX => write!(f, "{:?} ${:02X},X", name, v),
+            Mode::ZeroPageY => write!(f, "{:?} ${:02X},Y", name, v),
+            Mode::Absolute | Mode::Relative => write!(f, "{:?} ${:04X}", name, v),
+            Mode::AbsoluteX => write!(f, "{:?} ${:04X},X", name, v),
+            Mode::AbsoluteY => write!(f, "{:?} ${:04X},Y", name, v),
+            Mode::Indirect => write!(f, "{:?} (${:04X})", name, v),
+            Mode::IndirectX => write!(f, "{:?} (${:02X},X)", name, v),
+            Mode::IndirectY => write!(f, "{:?} (${:02X}),Y", name, v),
+        }
+    }
+
     pub fn parse(rom: &[u8], point: u16) -> Self {
         let point_usize = point as usize;
         let order_set_index = rom[point_usize];
@@ -1,5 +1,5 @@
 mod sprite;
 mod sprites;
 
-pub use sprite::Sprite;
+pub use sprite::{Sprite, UNIT_SPRITE_LENGTH};
 pub use sprites::Sprites;
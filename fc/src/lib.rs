@@ -0,0 +1,14 @@
+extern crate bytes;
+extern crate image;
+extern crate once_cell;
+
+pub mod binary;
+pub mod cpu;
+pub mod crash;
+pub mod display;
+pub mod error;
+pub mod ines;
+pub mod io;
+pub mod ppu;
+pub mod scheduler;
+pub mod trace;
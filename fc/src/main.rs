@@ -1,14 +1,124 @@
-extern crate bytes;
-extern crate image;
-extern crate once_cell;
-
-mod binary;
-mod cpu;
-mod display;
-mod ines;
-mod io;
-mod ppu;
+use fc::{cpu, crash, error, ines, io, trace};
+use std::fs::File;
+use std::io::BufWriter;
 
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(|s| s.as_str()) {
+        Some("trace") => trace(&args[2..]),
+        Some("trace-diff") => trace_diff(&args[2..]),
+        _ => println!("Hello, world!"),
+    }
+}
+
+/// fc trace <rom> <out> [--fceux] [--steps N] [--pc ADDR] [--fault ignore|log|break] [--crash PATH] [--history N] [--power-on zero|ff|fceux|random:SEED]
+/// romを実行し、トレースログをoutに書き出します。
+/// nestest.nesは--pc c000で自動テストモードになります。
+/// Fault(--fault breakのとき)、JAM、パニックで止まった場合は、クラッシュレポートを書き出します。
+/// 既定の書き出し先は<out>.crashです。
+fn trace(args: &[String]) {
+    const USAGE: &str = "usage: fc trace <rom> <out> [--fceux] [--steps N] [--pc ADDR] [--fault ignore|log|break] [--crash PATH] [--history N] [--power-on zero|ff|fceux|random:SEED]";
+    let (rom, out) = match args {
+        [rom, out, ..] => (rom, out),
+        _ => panic!("{}", USAGE),
+    };
+    let mut format = trace::TraceFormat::Nestest;
+    let mut steps = 10000;
+    let mut pc = None;
+    let mut fault_policy = error::FaultPolicy::default();
+    let mut crash_path = format!("{}.crash", out);
+    let mut history = cpu::history::HISTORY_SIZE;
+    let mut power_on = cpu::power_on::PowerOnPolicy::default();
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--fceux" => format = trace::TraceFormat::Fceux,
+            "--steps" => steps = options.next().and_then(|v| v.parse().ok()).expect(USAGE),
+            "--pc" => {
+                pc = options
+                    .next()
+                    .and_then(|v| u16::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+                    .or_else(|| panic!("{}", USAGE))
+            }
+            "--fault" => {
+                fault_policy = match options.next().map(|v| v.as_str()) {
+                    Some("ignore") => error::FaultPolicy::Ignore,
+                    Some("log") => error::FaultPolicy::Log,
+                    Some("break") => error::FaultPolicy::Break,
+                    _ => panic!("{}", USAGE),
+                }
+            }
+            "--crash" => crash_path = options.next().expect(USAGE).clone(),
+            "--history" => history = options.next().and_then(|v| v.parse().ok()).expect(USAGE),
+            "--power-on" => {
+                power_on = match options.next().map(|v| v.split_once(':').unwrap_or((v, ""))) {
+                    Some(("zero", "")) => cpu::power_on::PowerOnPolicy::Zero,
+                    Some(("ff", "")) => cpu::power_on::PowerOnPolicy::Ff,
+                    Some(("fceux", "")) => cpu::power_on::PowerOnPolicy::Fceux,
+                    Some(("random", seed)) => {
+                        cpu::power_on::PowerOnPolicy::Random(seed.parse().expect(USAGE))
+                    }
+                    _ => panic!("{}", USAGE),
+                }
+            }
+            _ => panic!("{}", USAGE),
+        }
+    }
+
+    let mut contents = io::read_to_binary(rom).expect("failed to read rom");
+    let ines = ines::parser(&mut contents).expect("failed to parse rom");
+    let rom = crash::RomInfo::new(&ines);
+    let mut cpu = cpu::Cpu::new(ines);
+    cpu.fault_policy = fault_policy;
+    cpu.history = cpu::history::History::new(history);
+    cpu.power_on = power_on;
+    cpu.power_cycle();
+    if let Some(pc) = pc {
+        cpu.register.PC = pc;
+    }
+    let out = File::create(out).expect("failed to create trace file");
+    let mut tracer = trace::Tracer::new(BufWriter::new(out), format);
+    for _ in 0..steps {
+        // PPUはバスの中でCPUの1サイクルごとに3ドット進んでいる
+        tracer
+            .trace(&cpu, cpu.bus.scheduler.position())
+            .expect("failed to write trace");
+        if let Err(cause) = crash::step(&mut cpu) {
+            let report = crash::CrashReport {
+                cause,
+                rom: &rom,
+                cpu: &cpu,
+            };
+            report
+                .save(&crash_path)
+                .expect("failed to write crash report");
+            eprintln!("[Crash]{} (report: {})", report.cause, crash_path);
+            break;
+        }
+    }
+}
+
+/// fc trace-diff <ours> <reference> [--context N]
+/// 2つのトレースログを比較し、最初に食い違った命令を前後N行と共に表示します。
+fn trace_diff(args: &[String]) {
+    const USAGE: &str = "usage: fc trace-diff <ours> <reference> [--context N]";
+    let (ours, reference, options) = match args {
+        [ours, reference, options @ ..] => (ours, reference, options),
+        _ => panic!("{}", USAGE),
+    };
+    let context = match options {
+        [] => 5,
+        [option, n] if option == "--context" => n.parse().expect(USAGE),
+        _ => panic!("{}", USAGE),
+    };
+
+    let ours = std::fs::read_to_string(ours).expect("failed to read trace");
+    let reference = std::fs::read_to_string(reference).expect("failed to read trace");
+    match trace::diff(&ours, &reference, context) {
+        Some(divergence) => {
+            print!("{}", divergence);
+            std::process::exit(1);
+        }
+        None => println!("no divergence"),
+    }
 }
@@ -0,0 +1,96 @@
+const BASE_NAMETABLE: u8 = 0b0000_0011;
+const INCREMENT: u8 = 0b0000_0100;
+const SPRITE_PATTERN_TABLE: u8 = 0b0000_1000;
+const BACKGROUND_PATTERN_TABLE: u8 = 0b0001_0000;
+const SPRITE_SIZE: u8 = 0b0010_0000;
+const NMI_ENABLE: u8 = 0b1000_0000;
+
+/// パターンテーブル1の先頭アドレス
+const PATTERN_TABLE1: u16 = 0x1000;
+
+/// PPUCTRL($2000)
+/// 7  bit  0
+/// VPHB SINN
+/// |||| ||++- ベースネームテーブル(0: $2000, 1: $2400, 2: $2800, 3: $2C00)
+/// |||| |+--- PPUDATAの読み書き後のアドレスの増分(0: +1, 1: +32)
+/// |||| +---- 8x8スプライトのパターンテーブル(0: $0000, 1: $1000)
+/// |||+------ 背景のパターンテーブル(0: $0000, 1: $1000)
+/// ||+------- スプライトの大きさ(0: 8x8, 1: 8x16)
+/// |+-------- PPUのマスター/スレーブ選択(NESでは使わない)
+/// +--------- VBlankの開始時にNMIを発生させる
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ControlRegister(pub u8);
+
+impl ControlRegister {
+    /// ベースネームテーブルの番号(0～3)
+    pub fn base_nametable(&self) -> usize {
+        (self.0 & BASE_NAMETABLE) as usize
+    }
+
+    /// PPUDATAの読み書き後に進めるアドレスの量
+    /// 1なら横方向、32ならネームテーブルの縦方向に進む。
+    pub fn increment(&self) -> u16 {
+        if self.0 & INCREMENT == INCREMENT {
+            32
+        } else {
+            1
+        }
+    }
+
+    /// 8x8スプライトのパターンテーブルの先頭アドレス
+    /// 8x16スプライトではタイル番号のビット0で選ぶので、この値は使わない。
+    pub fn sprite_pattern_table(&self) -> u16 {
+        if self.0 & SPRITE_PATTERN_TABLE == SPRITE_PATTERN_TABLE {
+            PATTERN_TABLE1
+        } else {
+            0x0000
+        }
+    }
+
+    /// 背景のパターンテーブルの先頭アドレス
+    pub fn background_pattern_table(&self) -> u16 {
+        if self.0 & BACKGROUND_PATTERN_TABLE == BACKGROUND_PATTERN_TABLE {
+            PATTERN_TABLE1
+        } else {
+            0x0000
+        }
+    }
+
+    /// スプライトの高さ(8か16)
+    pub fn sprite_height(&self) -> u8 {
+        if self.0 & SPRITE_SIZE == SPRITE_SIZE {
+            16
+        } else {
+            8
+        }
+    }
+
+    /// VBlankの間にNMIを発生させるか
+    pub fn nmi(&self) -> bool {
+        self.0 & NMI_ENABLE == NMI_ENABLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_control_register() {
+        let ctrl = ControlRegister(0b0000_0000);
+        assert_eq!(ctrl.base_nametable(), 0);
+        assert_eq!(ctrl.increment(), 1);
+        assert_eq!(ctrl.sprite_pattern_table(), 0x0000);
+        assert_eq!(ctrl.background_pattern_table(), 0x0000);
+        assert_eq!(ctrl.sprite_height(), 8);
+        assert!(!ctrl.nmi());
+
+        let ctrl = ControlRegister(0b1011_1110);
+        assert_eq!(ctrl.base_nametable(), 2);
+        assert_eq!(ctrl.increment(), 32);
+        assert_eq!(ctrl.sprite_pattern_table(), 0x1000);
+        assert_eq!(ctrl.background_pattern_table(), 0x1000);
+        assert_eq!(ctrl.sprite_height(), 16);
+        assert!(ctrl.nmi());
+    }
+}
@@ -0,0 +1,11 @@
+//! テスト用のキャラクターROM
+
+use crate::ines::sprite::{Sprite, UNIT_SPRITE_LENGTH};
+
+/// タイル0は透明、タイル1は全面が色番号1
+pub(crate) fn characters() -> Vec<Sprite> {
+    let mut raw = vec![0u8; UNIT_SPRITE_LENGTH];
+    raw.extend_from_slice(&[0xff; 8]);
+    raw.extend_from_slice(&[0x00; 8]);
+    vec![Sprite::parse(&raw, 0), Sprite::parse(&raw, 16)]
+}
@@ -1,5 +1,8 @@
-use super::ppu::PPU;
-use crate::binary;
+use super::control_register::ControlRegister;
+use super::memory_map::MemoryMap;
+use super::scroll_register::ScrollRegister;
+use crate::binary::DisplayBinary;
+use crate::error::EmuError;
 
 pub const PPUCTRL_INDEX: u16 = 0x2000;
 pub const PPUMASK_INDEX: u16 = 0x2001;
@@ -10,17 +13,113 @@ pub const PPUSCROLL_INDEX: u16 = 0x2005;
 pub const PPUADDR_INDEX: u16 = 0x2006;
 pub const PPUDATA_INDEX: u16 = 0x2007;
 
-pub type PPUADDRRegister = (u8, u8);
+/// スプライト64個分の属性
+pub const OAM_SIZE: usize = 0x100;
 
+/// OAM(Object Attribute Memory)
+/// スプライト1つにつきY座標、タイル番号、属性、X座標の4バイトを持つ。
+/// CPUからはOAMADDRとOAMDATA、またはOAM DMA($4014)を通してアクセスする。
+#[derive(PartialEq, Eq, Debug)]
+pub struct ObjectAttributeMemory(pub [u8; OAM_SIZE]);
+
+impl Default for ObjectAttributeMemory {
+    fn default() -> Self {
+        ObjectAttributeMemory([0u8; OAM_SIZE])
+    }
+}
+
+/// 1にされたビットが0に戻るまでのフレーム数(約600ms)
+pub const LATCH_DECAY_FRAMES: u8 = 36;
+
+/// PPUのI/Oラッチ
+/// $2000～$2007の読み書きでPPUのデータバスに乗った値を保持する。
+/// 書き込み専用のレジスタを読み込むと、この値が返る。
+/// 実機ではキャパシタなので、1にされたビットはしばらく1にされないと0に戻る。
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct IOLatch {
+    pub value: u8,
+    /// ビットごとの、最後に1にされてからのフレーム数
+    age: [u8; 8],
+}
+
+impl IOLatch {
+    /// maskのビットだけをvで更新します。
+    pub fn drive(&mut self, v: u8, mask: u8) {
+        self.value = (self.value & !mask) | (v & mask);
+        for (i, age) in self.age.iter_mut().enumerate() {
+            if v & mask & (1 << i) != 0 {
+                *age = 0;
+            }
+        }
+    }
+
+    /// 1フレームごとに呼び出してください。
+    pub fn decay(&mut self) {
+        for (i, age) in self.age.iter_mut().enumerate() {
+            if self.value & (1 << i) == 0 {
+                continue;
+            }
+            *age += 1;
+            if *age >= LATCH_DECAY_FRAMES {
+                self.value &= !(1 << i);
+            }
+        }
+    }
+}
+
+/// PPUSTATUSのVBlankフラグ
+const VBLANK: u8 = 0b1000_0000;
+/// PPUSTATUSのスプライト0ヒットフラグ
+const SPRITE_ZERO_HIT: u8 = 0b0100_0000;
+/// PPUSTATUSのスプライトオーバーフローフラグ
+const SPRITE_OVERFLOW: u8 = 0b0010_0000;
+
+/// VBlankが始まるスキャンライン
+/// 1ドット目でVBlankフラグが立つ。
+pub const VBLANK_SCANLINE: u16 = 241;
+/// VBlankフラグが立ってから、PPUSTATUSの読み込みでNMIを取り消せるドット
+/// この間に読み込むとフラグは1が見えるが、NMIは発生しない。
+const NMI_RACE_DOTS: std::ops::RangeInclusive<u16> = 1..=2;
+/// プリレンダースキャンライン
+/// 1ドット目でPPUSTATUSのフラグがすべて下りる。
+pub const PRE_RENDER_SCANLINE: u16 = 261;
+
+/// PPUMASKの背景とスプライトの表示ビット
+/// どちらかが立っていればレンダリング中とみなす。
+const RENDERING: u8 = 0b0001_1000;
+/// 可視スキャンラインの数
+pub const VISIBLE_SCANLINES: u16 = 240;
+
+/// パレットの先頭アドレス
+/// これ以降はリードバッファを経由せずに読み込める。
+const PALETTE_START: u16 = 0x3f00;
+
+/// PPUDATAのリードバッファ
+/// VRAMの読み込みは1回遅れて返る。
+/// パレットだけは直接返り、バッファにはその下にあるネームテーブルの値が入る。
 #[derive(PartialEq, Eq, Debug, Default)]
 pub struct PPUDATARegister {
-    pub cached: Vec<(u16, u8)>,
-    pub data: u8,
+    pub buffer: u8,
 }
 
 impl PPUDATARegister {
-    pub fn clear(&mut self) {
-        self.cached = vec![];
+    /// addrを読み込み、CPUに返す値を返します。
+    pub fn read(&mut self, vram: &MemoryMap, addr: u16) -> u8 {
+        if addr >= PALETTE_START {
+            self.buffer = vram.read(addr - 0x1000);
+            vram.read(addr)
+        } else {
+            std::mem::replace(&mut self.buffer, vram.read(addr))
+        }
+    }
+
+    /// 副作用なしに、次に読み込んだときに返る値を返します。
+    pub fn peek(&self, vram: &MemoryMap, addr: u16) -> u8 {
+        if addr >= PALETTE_START {
+            vram.read(addr)
+        } else {
+            self.buffer
+        }
     }
 }
 
@@ -30,75 +129,207 @@ impl PPUDATARegister {
 #[derive(PartialEq, Eq, Debug, Default)]
 pub struct IORegister {
     /// 0x2000 	PPUCTRL 	W 	コントロールレジスタ1
-    pub PPUCTRL: u8,
+    pub PPUCTRL: ControlRegister,
     /// 0x2001 	PPUMASK 	W 	コントロールレジスタ2
     pub PPUMASK: u8,
     /// 0x2002 	PPUSTATUS 	R 	PPUステータス
+    /// ビット7がVBlank、ビット6がスプライト0ヒット、ビット5がスプライトオーバーフロー
+    /// 読み込むとVBlankフラグと書き込みトグルがクリアされる。
     pub PPUSTATUS: u8,
     /// 0x2003 	OAMADDR 	W 	スプライトメモリデータ
     pub OAMADDR: u8,
     /// 0x2004 	OAMDATA 	RW 	デシマルモード
-    pub OAMDATA: u8,
-    /// 0x2005 	PPUSCROLL 	W 	背景スクロールオフセット
-    pub PPUSCROLL: u8,
-    /// 0x2006 	PPUADDR 	W 	PPUメモリアドレス
-    /// $2007を経由してPPUメモリへ書き込む16ビットアドレスを指定する。
-    /// 上位8ビット、下位8ビットの順に書き込む。
-    pub PPUADDR: PPUADDRRegister,
+    /// OAMADDRが指すOAMを読み書きする。書き込むとOAMADDRは1つ進む。
+    pub OAM: ObjectAttributeMemory,
+    /// 0x2005 PPUSCROLL W 背景スクロールオフセット
+    /// 0x2006 PPUADDR W PPUメモリアドレス
+    /// どちらも値を持たず、内部のスクロールレジスタを書き換える。
+    /// PPUADDRは$2007を経由してPPUメモリへ読み書きする14ビットアドレスを、上位8ビット、下位8ビットの順に指定する。
+    pub scroll: ScrollRegister,
     /// 0x2007 	PPUDATA 	RW 	PPUメモリデータ
     /// $2006によって指定されたPPUメモリアドレスへデータを書き込む。
     /// 書き込む度にメモリアドレスはインクリメント($2000のビット2によって+=1、+=32)する
+    /// 読み込みはリードバッファを経由する。
     pub PPUDATA: PPUDATARegister,
+    /// PPUのVRAM
+    /// PPUDATAの読み書きはすぐにここへ反映される。
+    pub vram: MemoryMap,
+    /// PPUの現在位置(scanline, dot)
+    /// VBlankの開始とPPUSTATUSの読み込みが重なったときの挙動に使う。
+    pub position: (u16, u16),
+    /// VBlankの開始直前にPPUSTATUSが読み込まれたので、このフレームはVBlankフラグを立てない
+    suppress_vblank: bool,
+    /// 最後にPPUのデータバスに乗った値
+    pub latch: IOLatch,
 }
 
 /// reference: http://pgate1.at-ninja.jp/NES_on_FPGA/nes_cpu.htm#instruction
 impl IORegister {
-    pub fn read(&self, i: u16) -> u8 {
+    /// 書き込み専用のレジスタはI/Oラッチの値を返します。
+    pub fn read(&mut self, i: u16) -> u8 {
+        // PPUSTATUSは上位3ビットだけを駆動し、残りはラッチの値が見える
+        let (v, mask) = match i {
+            PPUSTATUS_INDEX => (self.read_status(), 0b1110_0000),
+            OAMDATA_INDEX => (self.OAM.0[self.OAMADDR as usize], 0xff),
+            PPUDATA_INDEX => {
+                let addr = self.ppudata_addr();
+                self.increment_addr();
+                (self.PPUDATA.read(&self.vram, addr), 0xff)
+            }
+            PPUCTRL_INDEX | PPUMASK_INDEX | OAMADDR_INDEX | PPUSCROLL_INDEX | PPUADDR_INDEX => {
+                (0x00, 0x00)
+            }
+            _ => panic!("index out of range"),
+        };
+        self.latch.drive(v, mask);
+        self.latch.value
+    }
+
+    /// 書き込み専用のレジスタもpanicせず、最後に書き込まれた値を返します。
+    /// PPUSCROLLとPPUADDRは値を持たないので、tに書き込まれているXスクロールと下位アドレスを返します。
+    pub fn peek(&self, i: u16) -> u8 {
         match i {
-            PPUCTRL_INDEX => panic!("PPUCTRL writeonly"),
-            PPUMASK_INDEX => panic!("PPUMASK writeonly"),
+            PPUCTRL_INDEX => self.PPUCTRL.0,
+            PPUMASK_INDEX => self.PPUMASK,
             PPUSTATUS_INDEX => self.PPUSTATUS,
-            OAMADDR_INDEX => panic!("OAMADDR writeonly"),
-            OAMDATA_INDEX => self.OAMDATA,
-            PPUSCROLL_INDEX => panic!("PPUSCROLL writeonly"),
-            PPUADDR_INDEX => panic!("PPUADDR writeonly"),
-            PPUDATA_INDEX => self.PPUDATA.data,
+            OAMADDR_INDEX => self.OAMADDR,
+            OAMDATA_INDEX => self.OAM.0[self.OAMADDR as usize],
+            PPUSCROLL_INDEX => ((self.scroll.t & 0x1f) << 3) as u8 | self.scroll.x,
+            PPUADDR_INDEX => self.scroll.t as u8,
+            PPUDATA_INDEX => self.PPUDATA.peek(&self.vram, self.ppudata_addr()),
             _ => panic!("index out of range"),
         }
     }
 
-    pub fn write(&mut self, i: u16, d: u8) {
+    pub fn write(&mut self, i: u16, d: u8) -> Result<(), EmuError> {
+        // 読み込み専用のレジスタへの書き込みでもラッチは更新される
+        self.latch.drive(d, 0xff);
         match i {
             PPUCTRL_INDEX => {
-                self.PPUCTRL = d;
+                self.PPUCTRL = ControlRegister(d);
+                self.scroll.write_ctrl(d);
             }
             PPUMASK_INDEX => {
                 self.PPUMASK = d;
             }
-            PPUSTATUS_INDEX => panic!("PPUSTATUS readonly"),
+            PPUSTATUS_INDEX => return Err(EmuError::WriteReadOnly { addr: i, data: d }),
             OAMADDR_INDEX => {
                 self.OAMADDR = d;
             }
             OAMDATA_INDEX => {
-                self.OAMDATA = d;
-            }
-            PPUSCROLL_INDEX => {
-                self.PPUSCROLL = d;
-            }
-            PPUADDR_INDEX => {
-                let (upper, lower) = self.PPUADDR;
-                self.PPUADDR = (lower, d);
+                self.OAM.0[self.OAMADDR as usize] = d;
+                self.OAMADDR = self.OAMADDR.wrapping_add(1);
             }
+            PPUSCROLL_INDEX => self.scroll.write_scroll(d),
+            PPUADDR_INDEX => self.scroll.write_addr(d),
             PPUDATA_INDEX => {
-                let (upper, lower) = self.PPUADDR;
-                let addr = binary::u8u8_to_u16(upper, lower);
-                // println!("write memory {} {}", addr, d);
-                self.PPUDATA.cached.push((addr, d));
-                self.PPUDATA.data = d;
-                self.PPUADDR = binary::u16_to_u8u8(addr + 1);
+                let addr = self.ppudata_addr();
+                self.vram.write(addr, d);
+                self.increment_addr();
             }
             _ => panic!("index out of range"),
         }
+        Ok(())
+    }
+
+    /// PPUDATAが読み書きするVRAMのアドレス
+    fn ppudata_addr(&self) -> u16 {
+        self.scroll.v & 0x3fff
+    }
+
+    /// PPUDATAの読み書き後にvを進めます。
+    /// レンダリング中はPPUCTRLの増分ではなく、粗いXとYが同時に進む。
+    fn increment_addr(&mut self) {
+        if self.rendering() {
+            self.scroll.increment_x();
+            self.scroll.increment_y();
+        } else {
+            self.scroll.v = self.scroll.v.wrapping_add(self.PPUCTRL.increment()) & 0x7fff;
+        }
+    }
+
+    /// PPUMASKで背景かスプライトの表示が有効になっているか
+    pub fn rendering_enabled(&self) -> bool {
+        self.PPUMASK & RENDERING != 0
+    }
+
+    /// 背景かスプライトを描画しているスキャンラインか
+    fn rendering(&self) -> bool {
+        let (scanline, _) = self.position;
+        self.rendering_enabled()
+            && (scanline < VISIBLE_SCANLINES || scanline == PRE_RENDER_SCANLINE)
+    }
+
+    /// PPUSTATUSを読み込み、VBlankフラグと書き込みトグルをクリアします。
+    fn read_status(&mut self) -> u8 {
+        let v = self.PPUSTATUS;
+        // VBlankフラグが立つ1ドット前に読むと、このフレームはフラグが立たずNMIも発生しない
+        // 立った直後(NMI_RACE_DOTS)に読んだ場合は、NMIを出力する前にフラグが下りるのでNMIは発生しない
+        if self.position == (VBLANK_SCANLINE, 0) {
+            self.suppress_vblank = true;
+        }
+        self.PPUSTATUS &= !VBLANK;
+        self.scroll.reset_toggle();
+        v
+    }
+
+    /// PPUの位置を更新し、その位置で起きるPPUSTATUSの変化を反映します。
+    /// PPUは1ドット進むごとに呼び出してください。
+    pub fn update_status(&mut self, scanline: u16, dot: u16) {
+        self.position = (scanline, dot);
+        match (scanline, dot) {
+            (VBLANK_SCANLINE, 1) => {
+                if !self.suppress_vblank {
+                    self.PPUSTATUS |= VBLANK;
+                }
+                self.suppress_vblank = false;
+            }
+            (PRE_RENDER_SCANLINE, 1) => {
+                self.PPUSTATUS &= !(VBLANK | SPRITE_ZERO_HIT | SPRITE_OVERFLOW);
+            }
+            _ => {}
+        }
+    }
+
+    /// レンダリング中のスクロールレジスタの更新を行います。
+    /// PPUは1ドット進むごとに呼び出してください。
+    /// タイルを8ドットごとに読み込むたびに粗いXを進め、ドット256でYを進め、ドット257で水平方向をtから戻す。
+    /// プリレンダースキャンラインのドット280～304では、垂直方向もtから戻す。
+    pub fn update_scroll(&mut self, scanline: u16, dot: u16) {
+        self.position = (scanline, dot);
+        if !self.rendering() {
+            return;
+        }
+        match dot {
+            256 => {
+                self.scroll.increment_x();
+                self.scroll.increment_y();
+            }
+            257 => self.scroll.copy_x(),
+            280..=304 if scanline == PRE_RENDER_SCANLINE => self.scroll.copy_y(),
+            1..=255 | 321..=336 if dot.is_multiple_of(8) => self.scroll.increment_x(),
+            _ => {}
+        }
+    }
+
+    /// スプライト0の不透明なピクセルが背景の不透明なピクセルに重なったときに、レンダラが呼び出します。
+    pub fn set_sprite_zero_hit(&mut self) {
+        self.PPUSTATUS |= SPRITE_ZERO_HIT;
+    }
+
+    /// 1つのスキャンラインに9個以上のスプライトがあるときに、レンダラが呼び出します。
+    pub fn set_sprite_overflow(&mut self) {
+        self.PPUSTATUS |= SPRITE_OVERFLOW;
+    }
+
+    /// PPUのNMI出力
+    /// VBlank中にPPUCTRLでNMIを有効にしている間アクティブになる。
+    /// CPUは立ち上がりでNMIを受け付けるので、VBlank中にNMIを有効にした場合もNMIが発生する。
+    /// VBlankフラグが立ってからNMI_RACE_DOTSの間は、読み込みで取り消されることがあるのでまだアクティブにしない。
+    pub fn nmi(&self) -> bool {
+        let (scanline, dot) = self.position;
+        let racing = scanline == VBLANK_SCANLINE && NMI_RACE_DOTS.contains(&dot);
+        self.PPUSTATUS & VBLANK == VBLANK && self.PPUCTRL.nmi() && !racing
     }
 }
 
@@ -106,17 +337,165 @@ impl std::fmt::Display for IORegister {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "PPUCTRL: {:#x}, \nPPUMASK: {:#x}, \nPPUSTATUS: {:#x}, \nOAMADDR: {:#x}, \nOAMDATA: {:#x}, \nPPUSCROLL: {:#x}, \nPPUADDR: ({:?}, {:?}), \nPPUDATA: {:#x}\ncached: {:?}",
-            self.PPUCTRL,
+            "PPUCTRL: {:#x}, \nPPUMASK: {:#x}, \nPPUSTATUS: {:#x}, \nOAMADDR: {:#x}, \nOAMDATA: {:#x}, \nscroll: {}, \nPPUDATA buffer: {:#x}\nlatch: {:#x}\nOAM:\n{}",
+            self.PPUCTRL.0,
             self.PPUMASK,
             self.PPUSTATUS,
             self.OAMADDR,
-            self.OAMDATA,
-            self.PPUSCROLL,
-            self.PPUADDR.0,
-            self.PPUADDR.1,
-            self.PPUDATA.data,
-            self.PPUDATA.cached,
+            self.OAM.0[self.OAMADDR as usize],
+            self.scroll,
+            self.PPUDATA.buffer,
+            self.latch.value,
+            DisplayBinary(&self.OAM.0),
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary;
+
+    #[test]
+    fn it_read_latch() {
+        let mut register = IORegister::default();
+        register.write(PPUCTRL_INDEX, 0x5a).unwrap();
+        assert_eq!(register.read(PPUMASK_INDEX), 0x5a);
+        // PPUSTATUSの下位5ビットはラッチの値
+        register.PPUSTATUS = 0x80;
+        assert_eq!(register.read(PPUSTATUS_INDEX), 0x9a);
+        assert_eq!(register.read(PPUADDR_INDEX), 0x9a);
+    }
+
+    #[test]
+    fn it_latch_decay() {
+        let mut latch = IOLatch::default();
+        latch.drive(0x81, 0xff);
+        for _ in 0..LATCH_DECAY_FRAMES - 1 {
+            latch.decay();
+        }
+        latch.drive(0x01, 0x01);
+        latch.decay();
+        // 1にし直したビットだけが残る
+        assert_eq!(latch.value, 0x01);
+        for _ in 0..LATCH_DECAY_FRAMES {
+            latch.decay();
+        }
+        assert_eq!(latch.value, 0x00);
+    }
+
+    fn set_addr(register: &mut IORegister, addr: u16) {
+        let (upper, lower) = binary::u16_to_u8u8(addr);
+        register.write(PPUADDR_INDEX, upper).unwrap();
+        register.write(PPUADDR_INDEX, lower).unwrap();
+    }
+
+    #[test]
+    fn it_ppudata_write() {
+        let mut register = IORegister::default();
+        set_addr(&mut register, 0x2000);
+        register.write(PPUDATA_INDEX, 0x12).unwrap();
+        register.write(PPUDATA_INDEX, 0x34).unwrap();
+        // 書き込んだ時点でVRAMに反映される
+        assert_eq!(register.vram.read(0x2000), 0x12);
+        assert_eq!(register.vram.read(0x2001), 0x34);
+        assert_eq!(register.scroll.v, 0x2002);
+    }
+
+    #[test]
+    fn it_ppudata_read_buffer() {
+        let mut register = IORegister::default();
+        register.vram.write(0x2000, 0x12);
+        register.vram.write(0x2001, 0x34);
+        register.PPUDATA.buffer = 0xff;
+        set_addr(&mut register, 0x2000);
+        // 1回目はバッファの古い値が返る
+        assert_eq!(register.peek(PPUDATA_INDEX), 0xff);
+        assert_eq!(register.read(PPUDATA_INDEX), 0xff);
+        assert_eq!(register.read(PPUDATA_INDEX), 0x12);
+        assert_eq!(register.read(PPUDATA_INDEX), 0x34);
+    }
+
+    #[test]
+    fn it_ppudata_increment() {
+        let mut register = IORegister::default();
+        register.write(PPUCTRL_INDEX, 0b0000_0100).unwrap();
+        set_addr(&mut register, 0x2000);
+        register.write(PPUDATA_INDEX, 0x12).unwrap();
+        register.write(PPUDATA_INDEX, 0x34).unwrap();
+        // ネームテーブルの縦方向に書き込む
+        assert_eq!(register.vram.read(0x2000), 0x12);
+        assert_eq!(register.vram.read(0x2020), 0x34);
+        assert_eq!(register.scroll.v, 0x2040);
+    }
+
+    #[test]
+    fn it_nmi() {
+        let mut register = IORegister::default();
+        register.update_status(VBLANK_SCANLINE, 1);
+        assert!(!register.nmi());
+        register.write(PPUCTRL_INDEX, 0x80).unwrap();
+        // フラグが立ってから2ドットの間はまだ出力しない
+        assert!(!register.nmi());
+        register.update_status(VBLANK_SCANLINE, 3);
+        assert!(register.nmi());
+        register.update_status(PRE_RENDER_SCANLINE, 1);
+        assert!(!register.nmi());
+    }
+
+    #[test]
+    fn it_status() {
+        let mut register = IORegister::default();
+        register.update_status(VBLANK_SCANLINE, 1);
+        register.set_sprite_zero_hit();
+        register.set_sprite_overflow();
+        register.write(PPUADDR_INDEX, 0x21).unwrap();
+        assert!(register.scroll.w);
+        // 読み込むとVBlankフラグと書き込みトグルがクリアされる
+        assert_eq!(register.read(PPUSTATUS_INDEX) & 0xe0, 0xe0);
+        assert!(!register.scroll.w);
+        assert_eq!(register.read(PPUSTATUS_INDEX) & 0xe0, 0x60);
+        // プリレンダースキャンラインですべてクリアされる
+        register.update_status(PRE_RENDER_SCANLINE, 1);
+        assert_eq!(register.PPUSTATUS, 0x00);
+    }
+
+    #[test]
+    fn it_update_scroll() {
+        let mut register = IORegister {
+            PPUMASK: 0x18,
+            ..Default::default()
+        };
+        register.write(PPUSCROLL_INDEX, 0x08).unwrap();
+        register.write(PPUSCROLL_INDEX, 0x10).unwrap();
+        // プリレンダースキャンラインでtがvへコピーされる
+        for dot in 0..341 {
+            register.update_scroll(PRE_RENDER_SCANLINE, dot);
+        }
+        // 次のラインの最初の2タイルを先読みした位置
+        assert_eq!(register.scroll.v, 0x0043);
+        for dot in 0..341 {
+            register.update_scroll(0, dot);
+        }
+        assert_eq!(register.scroll.v, 0x1043);
+        assert_eq!(register.scroll.scroll_y(), 0x11);
+
+        // レンダリング中のPPUDATAの読み書きは粗いXとYを進める
+        register.write(PPUDATA_INDEX, 0x00).unwrap();
+        assert_eq!(register.scroll.v, 0x2044);
+        register.PPUMASK = 0x00;
+        register.write(PPUDATA_INDEX, 0x00).unwrap();
+        assert_eq!(register.scroll.v, 0x2045);
+    }
+
+    #[test]
+    fn it_ppudata_read_palette() {
+        let mut register = IORegister::default();
+        register.vram.write(0x2f00, 0x12);
+        register.vram.write(0x3f00, 0x0f);
+        set_addr(&mut register, 0x3f00);
+        // パレットは直接返り、バッファにはその下のネームテーブルが入る
+        assert_eq!(register.read(PPUDATA_INDEX), 0x0f);
+        assert_eq!(register.PPUDATA.buffer, 0x12);
+    }
+}
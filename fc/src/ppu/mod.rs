@@ -1,7 +1,11 @@
 pub mod color;
+pub mod control_register;
+#[cfg(test)]
+pub(crate) mod fixture;
 pub mod io_register;
 pub mod memory_map;
 mod ppu;
+pub mod scroll_register;
 
 pub use io_register::IORegister;
 pub use ppu::PPU;
@@ -1,54 +1,288 @@
-use super::io_register::IORegister;
-use super::memory_map::{MemoryMap, NAME_LENGTH};
+use super::io_register::{IORegister, PRE_RENDER_SCANLINE, VISIBLE_SCANLINES};
+use super::memory_map::NAME_LENGTH;
 use crate::display::Display;
-use crate::ines::sprite::Sprite;
+use crate::ines::sprite::{Sprite, UNIT_SPRITE_LENGTH};
+
+/// PPUMASKのビット
+const SHOW_BACKGROUND_LEFT: u8 = 0b0000_0010;
+const SHOW_SPRITES_LEFT: u8 = 0b0000_0100;
+const SHOW_BACKGROUND: u8 = 0b0000_1000;
+const SHOW_SPRITES: u8 = 0b0001_0000;
+
+/// 1つのスキャンラインに表示できるスプライトの数
+const SPRITES_PER_SCANLINE: usize = 8;
+/// 1スキャンラインのドット数
+pub const DOTS_PER_SCANLINE: u16 = 341;
+/// スプライトを評価するドット
+/// スキャンラインの描画を始める前で、垂直方向のスクロールはこのスキャンラインを指している
+const EVALUATE_DOT: u16 = 0;
+/// 1画面の幅と高さ(ピクセル)
+const SCREEN_WIDTH: usize = 256;
+const SCREEN_HEIGHT: usize = VISIBLE_SCANLINES as usize;
 
 /// CPUのMemoryMap上にあるIORegister
 /// カセットのキャラクターROM
 /// PPUのVRAMと連携して動いている
+/// 1フレームは341ドット x 262スキャンラインで、tickで1ドットずつ進む。
 #[derive(PartialEq, Eq, Debug)]
-pub struct PPU<'a> {
-    characters: &'a [Sprite],
-    memory: MemoryMap,
-    frame: usize,
+pub struct PPU {
+    characters: Vec<Sprite>,
+    /// 次に処理するスキャンライン(0～261)
+    /// 0～239が可視スキャンライン、241からVBlank、261がプリレンダースキャンライン
+    pub scanline: u16,
+    /// 次に処理するドット(0～340)
+    pub dot: u16,
+    /// 電源投入からのフレーム数
+    pub frame: u64,
+    /// このスキャンラインでスプライト0ヒットのフラグを立てるドット
+    sprite_zero_hit: Option<u16>,
 }
 
-impl<'a> PPU<'a> {
-    pub fn new(sprites: &'a [Sprite]) -> Self {
+impl PPU {
+    pub fn new(sprites: Vec<Sprite>) -> Self {
         PPU {
             characters: sprites,
-            memory: MemoryMap::new(),
-            frame: Default::default(),
+            scanline: 0,
+            dot: 0,
+            frame: 0,
+            sprite_zero_hit: None,
         }
     }
 
-    /// registerの内容を反映
-    pub fn refresh(&mut self, register: &mut IORegister) {
-        for (addr, d) in register.PPUDATA.cached.iter() {
-            dbg!((*addr, *d));
-            self.memory.write(*addr, *d);
+    /// 電源投入時の位置に戻します。CHR-ROMはそのまま残します。
+    pub fn power_on(&mut self) {
+        self.scanline = 0;
+        self.dot = 0;
+        self.frame = 0;
+        self.sprite_zero_hit = None;
+    }
+
+    /// 1ドット進めます。
+    /// VBlankフラグとスクロールレジスタを更新し、可視スキャンラインではスプライトを評価します。
+    /// スプライト0ヒットは、重なったピクセルを描画するドットでフラグを立てます。
+    pub fn tick(&mut self, register: &mut IORegister) {
+        if self.scanline < VISIBLE_SCANLINES {
+            if self.dot == EVALUATE_DOT {
+                self.sprite_zero_hit = self.evaluate_sprites(register, self.scanline);
+            }
+            if self.sprite_zero_hit == Some(self.dot) {
+                register.set_sprite_zero_hit();
+            }
         }
-        register.PPUDATA.clear();
+        register.update_status(self.scanline, self.dot);
+        register.update_scroll(self.scanline, self.dot);
+
+        // 奇数フレームでレンダリングが有効なら、プリレンダースキャンラインの最後のドットを飛ばす
+        let skip = self.scanline == PRE_RENDER_SCANLINE
+            && self.frame % 2 == 1
+            && register.rendering_enabled();
+        let last = if skip {
+            DOTS_PER_SCANLINE - 2
+        } else {
+            DOTS_PER_SCANLINE - 1
+        };
+        if self.dot < last {
+            self.dot += 1;
+            return;
+        }
+        self.dot = 0;
+        if self.scanline < PRE_RENDER_SCANLINE {
+            self.scanline += 1;
+            return;
+        }
+        self.scanline = 0;
+        self.frame += 1;
+        // PPUのI/Oラッチは1フレームごとに減衰する
+        register.latch.decay();
     }
 
     /// 描画
-    pub fn draw(&mut self) {
-        let mut table: [[&Sprite; NAME_LENGTH]; 4] = [[&self.characters[0]; NAME_LENGTH]; 4];
-        let mut name_index = 0;
-        for names in [
-            self.memory.name0,
-            self.memory.name1,
-            self.memory.name2,
-            self.memory.name3,
-        ] {
-            let mut i = 0;
-            for sprites_index in names {
-                table[name_index][i] = &self.characters[sprites_index as usize];
-                i += 1;
+    /// VRAMはCPUからPPUDATAを通して書き込まれたregisterのものを使う。
+    pub fn draw(&mut self, register: &IORegister) {
+        let ctrl = register.PPUCTRL;
+        // パターンテーブル1のタイルは256番目以降にある
+        let offset = ctrl.background_pattern_table() as usize / UNIT_SPRITE_LENGTH;
+        let mut table: [&Sprite; NAME_LENGTH] = [&self.characters[0]; NAME_LENGTH];
+        for (i, name) in register.vram.name(ctrl.base_nametable()).iter().enumerate() {
+            table[i] = &self.characters[(offset + *name as usize) % self.characters.len()];
+        }
+        Display::draw(table, &format!("./tmp/{}.png", self.frame));
+    }
+
+    /// scanlineに表示するスプライトを評価し、PPUSTATUSのスプライトオーバーフローを立てます。
+    /// スプライト0ヒットが起きる場合は、最初に重なったピクセルXを描画するドット(X + 1)を返します。
+    /// 可視スキャンラインごとに、垂直方向のスクロールが進むドット256より前に呼び出してください。
+    /// オーバーフローは実機の評価のバグを再現せず、9個以上あれば立てます。
+    /// 背景の位置はスクロールレジスタから求めます。
+    pub fn evaluate_sprites(&self, register: &mut IORegister, scanline: u16) -> Option<u16> {
+        let mask = register.PPUMASK;
+        if mask & (SHOW_BACKGROUND | SHOW_SPRITES) == 0 || scanline >= VISIBLE_SCANLINES {
+            return None;
+        }
+        let height = register.PPUCTRL.sprite_height() as u16;
+        // OAMのY座標は表示位置の1ライン上
+        let in_range = |y: u8| scanline.wrapping_sub(y as u16 + 1) < height;
+        let count = register.OAM.0.chunks(4).filter(|s| in_range(s[0])).count();
+        if count > SPRITES_PER_SCANLINE {
+            register.set_sprite_overflow();
+        }
+
+        let sprite = &register.OAM.0[..4];
+        if mask & SHOW_BACKGROUND == 0 || mask & SHOW_SPRITES == 0 || !in_range(sprite[0]) {
+            return None;
+        }
+        let (y, tile, attribute, x) = (sprite[0], sprite[1], sprite[2], sprite[3]);
+        let mut row = scanline - (y as u16 + 1);
+        if attribute & 0x80 != 0 {
+            row = height - 1 - row;
+        }
+        // 8x16スプライトはタイル番号のビット0でパターンテーブルを選ぶ
+        let (table, tile) = if height == 16 {
+            (
+                (tile as u16 & 1) * 0x1000,
+                (tile & 0xfe) as usize + row as usize / 8,
+            )
+        } else {
+            (register.PPUCTRL.sprite_pattern_table(), tile as usize)
+        };
+        let (scroll_x, scroll_y) = (register.scroll.scroll_x(), register.scroll.scroll_y());
+        (0..8).find_map(|col| {
+            let px = x as usize + col;
+            // 右端のピクセルと、左端8ピクセルを隠しているときはヒットしない
+            let clipped = px < 8
+                && mask & (SHOW_BACKGROUND_LEFT | SHOW_SPRITES_LEFT)
+                    != SHOW_BACKGROUND_LEFT | SHOW_SPRITES_LEFT;
+            if px >= 255 || clipped {
+                return None;
             }
-            name_index += 1;
+            let col = if attribute & 0x40 != 0 { 7 - col } else { col };
+            let hit = self.pixel(table, tile, row as usize % 8, col) != 0
+                && self.background_pixel(register, scroll_x + px, scroll_y) != 0;
+            // ドット0は描画しないので、ピクセルXはドットX + 1で描画される
+            hit.then_some(px as u16 + 1)
+        })
+    }
+
+    /// 4画面分を並べた512x480の座標(x, y)にある背景のピクセルの色番号(0は透明)
+    fn background_pixel(&self, register: &IORegister, x: usize, y: usize) -> u8 {
+        let (x, y) = (x % (SCREEN_WIDTH * 2), y % (SCREEN_HEIGHT * 2));
+        let n = y / SCREEN_HEIGHT * 2 + x / SCREEN_WIDTH;
+        let (x, y) = (x % SCREEN_WIDTH, y % SCREEN_HEIGHT);
+        let name = register.vram.name(n)[y / 8 * 32 + x / 8] as usize;
+        let table = register.PPUCTRL.background_pattern_table();
+        self.pixel(table, name, y % 8, x % 8)
+    }
+
+    /// パターンテーブルのタイルのピクセルの色番号(0は透明)
+    /// CHR-RAMのカセットなどキャラクターROMがない場合は、すべて透明として扱う。
+    fn pixel(&self, table: u16, tile: usize, row: usize, col: usize) -> u8 {
+        if self.characters.is_empty() {
+            return 0;
         }
-        Display::draw(table[0], &format!("./tmp/{}.png", self.frame));
-        self.frame += 1;
+        let index = table as usize / UNIT_SPRITE_LENGTH + tile;
+        let Sprite(rows) = &self.characters[index % self.characters.len()];
+        Vec::<u8>::from(rows[row])[col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppu::fixture::characters;
+    use crate::ppu::io_register::PPUMASK_INDEX;
+
+    #[test]
+    fn it_sprite_zero_hit() {
+        let ppu = PPU::new(characters());
+        let mut register = IORegister::default();
+        register.PPUMASK = 0x1e;
+        // (16, 10)から8x8のスプライト0
+        register.OAM.0[..4].copy_from_slice(&[9, 1, 0, 16]);
+        // 背景が透明ならヒットしない
+        assert_eq!(ppu.evaluate_sprites(&mut register, 10), None);
+        register.vram.write(0x2000 + 32 + 2, 1);
+        assert_eq!(ppu.evaluate_sprites(&mut register, 9), None);
+        // 描画中のvは10ライン目(粗いY 1、細かいY 2)を指している
+        register.scroll.v = 0x2020;
+        assert_eq!(ppu.evaluate_sprites(&mut register, 10), Some(17));
+        // 背景が途中から不透明なら、最初に重なったピクセルで立てる
+        register.vram.write(0x2000 + 32 + 2, 0);
+        register.vram.write(0x2000 + 32 + 3, 1);
+        register.OAM.0[3] = 20;
+        assert_eq!(ppu.evaluate_sprites(&mut register, 10), Some(25));
+        // フラグはtickが立てる
+        assert_eq!(register.PPUSTATUS, 0x00);
+    }
+
+    #[test]
+    fn it_sprite_zero_hit_scroll() {
+        let ppu = PPU::new(characters());
+        let mut register = IORegister::default();
+        register.PPUMASK = 0x1e;
+        register.OAM.0[..4].copy_from_slice(&[9, 1, 0, 16]);
+        // ネームテーブル1の2行目の先頭のタイル
+        register.vram.write(0x2400 + 32, 1);
+        register.scroll.v = 0x2020;
+        assert_eq!(ppu.evaluate_sprites(&mut register, 10), None);
+        // X=244なので、画面のX=16～23はネームテーブル1の先頭の2タイルになる
+        register.scroll.write_scroll(0xf4);
+        register.scroll.write_scroll(0x00);
+        assert_eq!(ppu.evaluate_sprites(&mut register, 10), Some(17));
+    }
+
+    #[test]
+    fn it_tick() {
+        let mut ppu = PPU::new(characters());
+        let mut register = IORegister::default();
+        for _ in 0..241 * 341 + 1 {
+            ppu.tick(&mut register);
+        }
+        assert_eq!((ppu.scanline, ppu.dot), (241, 1));
+        assert_eq!(register.PPUSTATUS, 0x00);
+        ppu.tick(&mut register);
+        assert_eq!(register.PPUSTATUS, 0x80);
+        for _ in 0..21 * 341 - 2 {
+            ppu.tick(&mut register);
+        }
+        assert_eq!((ppu.scanline, ppu.dot, ppu.frame), (0, 0, 1));
+        assert_eq!(register.PPUSTATUS, 0x00);
+    }
+
+    #[test]
+    fn it_tick_odd_frame() {
+        let mut ppu = PPU::new(characters());
+        let mut register = IORegister::default();
+        register.write(PPUMASK_INDEX, 0x08).unwrap();
+        // 偶数フレームは341 x 262ドット、奇数フレームは1ドット短い
+        for _ in 0..341 * 262 {
+            ppu.tick(&mut register);
+        }
+        assert_eq!((ppu.scanline, ppu.dot, ppu.frame), (0, 0, 1));
+        for _ in 0..341 * 262 - 1 {
+            ppu.tick(&mut register);
+        }
+        assert_eq!((ppu.scanline, ppu.dot, ppu.frame), (0, 0, 2));
+        // レンダリングが無効なら飛ばさない
+        register.PPUMASK = 0x00;
+        for _ in 0..341 * 262 * 2 - 1 {
+            ppu.tick(&mut register);
+        }
+        assert_eq!((ppu.scanline, ppu.dot, ppu.frame), (261, 340, 3));
+    }
+
+    #[test]
+    fn it_sprite_overflow() {
+        let ppu = PPU::new(characters());
+        let mut register = IORegister::default();
+        register.OAM.0.fill(0xff);
+        for i in 0..9 {
+            register.OAM.0[i * 4] = 30;
+        }
+        // レンダリングが無効なら評価しない
+        assert_eq!(ppu.evaluate_sprites(&mut register, 31), None);
+        assert_eq!(register.PPUSTATUS, 0x00);
+        register.PPUMASK = 0x10;
+        assert_eq!(ppu.evaluate_sprites(&mut register, 31), None);
+        assert_eq!(register.PPUSTATUS, 0x20);
     }
 }
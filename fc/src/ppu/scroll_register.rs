@@ -0,0 +1,200 @@
+const COARSE_X: u16 = 0x001f;
+const COARSE_Y: u16 = 0x03e0;
+const NAMETABLE_X: u16 = 0x0400;
+const NAMETABLE_Y: u16 = 0x0800;
+const FINE_Y: u16 = 0x7000;
+/// ドット257でtからvへコピーする水平方向のビット
+const HORIZONTAL: u16 = NAMETABLE_X | COARSE_X;
+/// プリレンダースキャンラインでtからvへコピーする垂直方向のビット
+const VERTICAL: u16 = FINE_Y | NAMETABLE_Y | COARSE_Y;
+
+/// 1画面の幅と高さ(ピクセル)
+const SCREEN_WIDTH: usize = 256;
+const SCREEN_HEIGHT: usize = 240;
+
+/// PPU内部のスクロールレジスタ(いわゆるloopyレジスタ)
+/// PPUSCROLLとPPUADDRはどちらもtを書き換え、書き込みトグルwを共有する。
+/// vとtは15ビットで、次のように分かれている。
+/// yyy NN YYYYY XXXXX
+/// ||| || ||||| +++++- 粗いX(タイル単位)
+/// ||| || +++++------- 粗いY(タイル単位)
+/// ||| ++------------- ネームテーブル
+/// +++---------------- 細かいY(ピクセル単位)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ScrollRegister {
+    /// 現在のVRAMアドレス
+    /// PPUDATAの読み書きと、描画中のタイルの読み込みに使う。
+    pub v: u16,
+    /// 一時的なVRAMアドレス
+    /// 画面左上のタイルのアドレスで、描画中にvへコピーされる。
+    pub t: u16,
+    /// 細かいX(ピクセル単位)
+    pub x: u8,
+    /// 書き込みトグル
+    /// falseなら1回目、trueなら2回目の書き込み
+    pub w: bool,
+}
+
+impl ScrollRegister {
+    /// PPUCTRLへの書き込み
+    /// 下位2ビットがtのネームテーブルになる。
+    pub fn write_ctrl(&mut self, d: u8) {
+        self.t = (self.t & !(NAMETABLE_X | NAMETABLE_Y)) | ((d as u16 & 0x03) << 10);
+    }
+
+    /// PPUSCROLLへの書き込み
+    /// 1回目はX、2回目はYのスクロール量
+    pub fn write_scroll(&mut self, d: u8) {
+        if self.w {
+            let coarse_y = (d as u16 >> 3) << 5;
+            let fine_y = (d as u16 & 0x07) << 12;
+            self.t = (self.t & !(COARSE_Y | FINE_Y)) | coarse_y | fine_y;
+        } else {
+            self.t = (self.t & !COARSE_X) | (d as u16 >> 3);
+            self.x = d & 0x07;
+        }
+        self.w = !self.w;
+    }
+
+    /// PPUADDRへの書き込み
+    /// 上位、下位の順に書き込み、2回目でtがvへコピーされる。
+    pub fn write_addr(&mut self, d: u8) {
+        if self.w {
+            self.t = (self.t & 0xff00) | d as u16;
+            self.v = self.t;
+        } else {
+            // 上位2ビットは捨て、ビット14はクリアされる
+            self.t = (self.t & 0x00ff) | ((d as u16 & 0x3f) << 8);
+        }
+        self.w = !self.w;
+    }
+
+    /// PPUSTATUSの読み込みで書き込みトグルをリセットします。
+    pub fn reset_toggle(&mut self) {
+        self.w = false;
+    }
+
+    /// 粗いXを1つ進めます。右端を越えたら隣のネームテーブルへ移ります。
+    pub fn increment_x(&mut self) {
+        if self.v & COARSE_X == COARSE_X {
+            self.v &= !COARSE_X;
+            self.v ^= NAMETABLE_X;
+        } else {
+            self.v += 1;
+        }
+    }
+
+    /// 細かいYを1つ進めます。
+    /// 粗いYが29を越えたら0に戻り、下のネームテーブルへ移ります。
+    /// PPUSCROLLで30以上にした場合は属性テーブルを読み進め、31の次はネームテーブルを切り替えずに0に戻ります。
+    pub fn increment_y(&mut self) {
+        if self.v & FINE_Y != FINE_Y {
+            self.v += 0x1000;
+            return;
+        }
+        self.v &= !FINE_Y;
+        let coarse_y = match (self.v & COARSE_Y) >> 5 {
+            29 => {
+                self.v ^= NAMETABLE_Y;
+                0
+            }
+            31 => 0,
+            y => y + 1,
+        };
+        self.v = (self.v & !COARSE_Y) | (coarse_y << 5);
+    }
+
+    /// 水平方向の位置をtからvへコピーします。
+    pub fn copy_x(&mut self) {
+        self.v = (self.v & !HORIZONTAL) | (self.t & HORIZONTAL);
+    }
+
+    /// 垂直方向の位置をtからvへコピーします。
+    pub fn copy_y(&mut self) {
+        self.v = (self.v & !VERTICAL) | (self.t & VERTICAL);
+    }
+
+    /// スキャンラインの左端のX座標
+    /// 4画面分を並べた512x480の座標で、水平方向はドット257でコピーされたtの値を使う。
+    pub fn scroll_x(&self) -> usize {
+        let nametable = ((self.t & NAMETABLE_X) >> 10) as usize;
+        nametable * SCREEN_WIDTH + (self.t & COARSE_X) as usize * 8 + self.x as usize
+    }
+
+    /// 描画中のスキャンラインのY座標
+    /// 4画面分を並べた512x480の座標で、垂直方向はvの値を使う。
+    pub fn scroll_y(&self) -> usize {
+        let nametable = ((self.v & NAMETABLE_Y) >> 11) as usize;
+        let coarse_y = ((self.v & COARSE_Y) >> 5) as usize;
+        let fine_y = ((self.v & FINE_Y) >> 12) as usize;
+        nametable * SCREEN_HEIGHT + coarse_y * 8 + fine_y
+    }
+}
+
+impl std::fmt::Display for ScrollRegister {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "v: {:#06x}, t: {:#06x}, x: {}, w: {}",
+            self.v, self.t, self.x, self.w
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_write() {
+        let mut scroll = ScrollRegister::default();
+        scroll.write_ctrl(0x03);
+        assert_eq!(scroll.t, 0x0c00);
+        // X=0x7d(粗いX 15、細かいX 5)、Y=0x5e(粗いY 11、細かいY 6)
+        scroll.write_scroll(0x7d);
+        assert_eq!((scroll.t, scroll.x, scroll.w), (0x0c0f, 5, true));
+        scroll.write_scroll(0x5e);
+        assert_eq!((scroll.t, scroll.w), (0x6d6f, false));
+        assert_eq!(scroll.v, 0x0000);
+        // PPUADDRはtを書き換えて、2回目でvへコピーする
+        scroll.write_addr(0xff);
+        assert_eq!(scroll.t, 0x3f6f);
+        scroll.write_addr(0x10);
+        assert_eq!((scroll.t, scroll.v), (0x3f10, 0x3f10));
+    }
+
+    #[test]
+    fn it_increment() {
+        let mut scroll = ScrollRegister {
+            v: 0x001f,
+            ..Default::default()
+        };
+        scroll.increment_x();
+        assert_eq!(scroll.v, 0x0400);
+
+        // 細かいY 7、粗いY 29から下のネームテーブルへ
+        scroll.v = 0x73a0;
+        scroll.increment_y();
+        assert_eq!(scroll.v, 0x0800);
+        // 粗いY 31はネームテーブルを切り替えずに0へ戻る
+        scroll.v = 0x73e0;
+        scroll.increment_y();
+        assert_eq!(scroll.v, 0x0000);
+        scroll.increment_y();
+        assert_eq!(scroll.v, 0x1000);
+    }
+
+    #[test]
+    fn it_copy() {
+        let mut scroll = ScrollRegister {
+            t: 0x7fff,
+            ..Default::default()
+        };
+        scroll.copy_x();
+        assert_eq!(scroll.v, 0x041f);
+        scroll.copy_y();
+        assert_eq!(scroll.v, 0x7fff);
+        assert_eq!(scroll.scroll_x(), 256 + 31 * 8);
+        assert_eq!(scroll.scroll_y(), 240 + 31 * 8 + 7);
+    }
+}
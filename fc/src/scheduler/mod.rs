@@ -0,0 +1,3 @@
+mod scheduler;
+
+pub use scheduler::{Scheduler, DOTS_PER_CYCLE};
@@ -0,0 +1,169 @@
+use crate::ppu::{IORegister, PPU};
+
+/// NTSCでCPUの1サイクルの間にPPUが進むドット数
+pub const DOTS_PER_CYCLE: u64 = 3;
+
+/// CPUとPPUを同じクロックで動かします。
+/// MemoryMapが持ち、CpuがBus::tickでバスを1サイクル進めるたびにPPUを3ドット進めます。
+/// Cpuは読み書きの前にバスを進めるので、命令の途中のI/Oレジスタの読み書きもそのサイクルのPPUの位置で行われます。
+#[derive(PartialEq, Eq, Debug)]
+pub struct Scheduler {
+    pub ppu: PPU,
+}
+
+impl Scheduler {
+    pub fn new(ppu: PPU) -> Self {
+        Scheduler { ppu }
+    }
+
+    /// CPUの1サイクル分だけPPUを進めます。
+    pub fn tick(&mut self, register: &mut IORegister) {
+        for _ in 0..DOTS_PER_CYCLE {
+            self.ppu.tick(register);
+        }
+    }
+
+    /// PPUを電源投入時の位置に戻します。CHR-ROMはそのまま残します。
+    pub fn power_on(&mut self) {
+        self.ppu.power_on();
+    }
+
+    /// トレースログに表示するPPUの位置(scanline, dot)
+    pub fn position(&self) -> (u16, u16) {
+        (self.ppu.scanline, self.ppu.dot)
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler::new(PPU::new(vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::fixture::cpu_with_program;
+    use crate::cpu::interrupt::Interrupt;
+    use crate::cpu::Cpu;
+    use crate::ppu::fixture::characters;
+
+    #[test]
+    fn it_step() {
+        // RESETの7サイクルでPPUは(0, 21)まで進む
+        let mut cpu = cpu_with_program(&[0xea]);
+        assert_eq!(cpu.bus.scheduler.position(), (0, 21));
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.scheduler.position(), (0, 27));
+    }
+
+    #[test]
+    fn it_lockstep() {
+        // BIT $2002は4サイクル目に読み込むので、PPUは命令の先頭から12ドット進んでいる
+        let mut cpu = cpu_with_program(&[0x2c, 0x02, 0x20]);
+        cpu.bus.scheduler.ppu.scanline = 240;
+        cpu.bus.scheduler.ppu.dot = 335;
+        cpu.step().unwrap();
+        // 8ドット目の(241, 1)でVBlankフラグが立ち、(241, 5)で読み込んだ
+        assert!(cpu.register.P.n());
+        assert_eq!(cpu.bus.ppu.position, (241, 5));
+        assert_eq!(cpu.bus.peek(0x2002) & 0x80, 0x00);
+
+        // 8ドット早く始めると、フラグが立つ前の(240, 338)で読み込む
+        let mut cpu = cpu_with_program(&[0x2c, 0x02, 0x20]);
+        cpu.bus.scheduler.ppu.scanline = 240;
+        cpu.bus.scheduler.ppu.dot = 327;
+        cpu.step().unwrap();
+        assert!(!cpu.register.P.n());
+        assert_eq!(cpu.bus.ppu.position, (240, 338));
+    }
+
+    /// NMIを有効にして、(241, dot)でPPUSTATUSを読み込むようにBIT $2002を実行する
+    fn read_status_at(dot: u16) -> Cpu {
+        let mut cpu = cpu_with_program(&[
+            0x2c, 0x02, 0x20, // BIT $2002
+            0xea, // NOP
+        ]);
+        cpu.bus.write(0x2000, 0x80).unwrap();
+        // 読み込みまでに12ドット進む
+        cpu.bus.scheduler.ppu.scanline = 240;
+        cpu.bus.scheduler.ppu.dot = 330 + dot;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.ppu.position, (241, dot));
+        cpu
+    }
+
+    #[test]
+    fn it_status_race() {
+        // フラグが立つ1ドット前に読むと、このフレームはフラグもNMIも立たない
+        let mut cpu = read_status_at(0);
+        assert!(!cpu.register.P.n());
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        assert_eq!(cpu.bus.peek(0x2002) & 0x80, 0x00);
+
+        // 立った直後に読むと、フラグは見えるがNMIは発生しない
+        for dot in 1..=2 {
+            let mut cpu = read_status_at(dot);
+            assert!(cpu.register.P.n());
+            assert_eq!(cpu.step().unwrap().interrupt, None);
+        }
+
+        // それより後に読んでもNMIは取り消されない
+        let mut cpu = read_status_at(3);
+        assert!(cpu.register.P.n());
+        assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::NMI));
+    }
+
+    /// 10ライン目のX=0～31に不透明な背景を置き、スプライト0をXに置いて、(10, 11)でPPUSTATUSを読み込む
+    fn read_sprite_zero_hit(x: u8) -> Cpu {
+        let mut cpu = cpu_with_program(&[
+            0x2c, 0x02, 0x20, // BIT $2002
+            0xea, // NOP
+        ]);
+        cpu.bus.scheduler = Scheduler::new(PPU::new(characters()));
+        cpu.bus.ppu.PPUMASK = 0x1e;
+        cpu.bus.ppu.scroll.v = 0x2020;
+        for col in 0..4 {
+            cpu.bus.ppu.vram.write(0x2000 + 32 + col, 1);
+        }
+        cpu.bus.ppu.OAM.0[..4].copy_from_slice(&[9, 1, 0, x]);
+        // ドット0でスプライトを評価してから読み込むまでに12ドット進む
+        cpu.bus.scheduler.ppu.scanline = 10;
+        cpu.bus.scheduler.ppu.dot = 0;
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.ppu.position, (10, 11));
+        cpu
+    }
+
+    #[test]
+    fn it_sprite_zero_hit() {
+        // ピクセル11はドット12で描画されるので、(10, 11)ではまだフラグは立っていない
+        let mut cpu = read_sprite_zero_hit(11);
+        assert!(!cpu.register.P.v());
+        assert_eq!(cpu.bus.peek(0x2002) & 0x40, 0x00);
+        cpu.step().unwrap();
+        assert_eq!(cpu.bus.peek(0x2002) & 0x40, 0x40);
+
+        // ピクセル10はドット11で描画されるので、読み込みに間に合う
+        let cpu = read_sprite_zero_hit(10);
+        assert!(cpu.register.P.v());
+    }
+
+    #[test]
+    fn it_wait_vblank() {
+        let mut cpu = cpu_with_program(&[
+            0x2c, 0x02, 0x20, // BIT $2002
+            0x10, 0xfb, // BPL $8000
+            0x4c, 0x05, 0x80, // JMP $8005
+        ]);
+        while cpu.register.PC < 0x8005 {
+            cpu.step().unwrap();
+        }
+        // VBlankフラグが立ってからループ1周(7サイクル)ほどで抜け、フラグは読み込みで下りている
+        let (scanline, dot) = cpu.bus.scheduler.position();
+        assert_eq!(scanline, 241);
+        assert!(dot <= 1 + 2 * 7 * DOTS_PER_CYCLE as u16);
+        assert_eq!(cpu.bus.peek(0x2002) & 0x80, 0x00);
+        assert_eq!(cpu.bus.scheduler.ppu.frame, 0);
+    }
+}
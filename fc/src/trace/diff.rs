@@ -0,0 +1,278 @@
+/// トレースログの1行から比較に使う値を取り出したもの
+/// 次の書式を読み込めます。
+/// nestest: C000  4C F5 C5  JMP $C5F5   A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+/// FCEUX:   $C000:4C F5 C5  JMP $C5F5   A:00 X:00 Y:00 S:FD P:nvUbdIzc
+/// Mesen:   C000  JMP $C5F5   A:00 X:00 Y:00 S:FD P:nvubdIzc   V:0   H:21  Fr:0 Cycle:7
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TraceLine {
+    pub pc: u16,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub p: u8,
+    pub sp: u8,
+    pub cycles: Option<u64>,
+}
+
+/// Bフラグと未使用ビットはスタック上にしか存在せず、エミュレータによって表示が異なるため比較しません。
+const P_MASK: u8 = 0b1100_1111;
+
+impl TraceLine {
+    /// 命令の行でなければNoneを返します。
+    pub fn parse(line: &str) -> Option<Self> {
+        let head = line.split_whitespace().next()?;
+        let head = head.trim_start_matches('$');
+        let pc = u16::from_str_radix(head.get(..4)?, 16).ok()?;
+
+        let mut a = None;
+        let mut x = None;
+        let mut y = None;
+        let mut p = None;
+        let mut sp = None;
+        let mut cycles = None;
+        for token in line.split_whitespace() {
+            let (key, value) = match token.find(':') {
+                Some(i) => (&token[..i], &token[i + 1..]),
+                None => continue,
+            };
+            match key {
+                "A" => a = u8::from_str_radix(value, 16).ok(),
+                "X" => x = u8::from_str_radix(value, 16).ok(),
+                "Y" => y = u8::from_str_radix(value, 16).ok(),
+                "P" => p = parse_flags(value),
+                "SP" | "S" => sp = u8::from_str_radix(value, 16).ok(),
+                "CYC" | "Cycle" => cycles = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        Some(TraceLine {
+            pc,
+            a: a?,
+            x: x?,
+            y: y?,
+            p: p?,
+            sp: sp?,
+            cycles,
+        })
+    }
+
+    /// 値が異なる項目の名前
+    pub fn diff(&self, other: &TraceLine) -> Vec<&'static str> {
+        let mut fields = vec![];
+        if self.pc != other.pc {
+            fields.push("PC");
+        }
+        if self.a != other.a {
+            fields.push("A");
+        }
+        if self.x != other.x {
+            fields.push("X");
+        }
+        if self.y != other.y {
+            fields.push("Y");
+        }
+        if self.p & P_MASK != other.p & P_MASK {
+            fields.push("P");
+        }
+        if self.sp != other.sp {
+            fields.push("SP");
+        }
+        if let (Some(c1), Some(c2)) = (self.cycles, other.cycles) {
+            if c1 != c2 {
+                fields.push("CYC");
+            }
+        }
+        fields
+    }
+}
+
+/// 24のような16進数か、nvUbdIzcのようにセットされたフラグを大文字で表したもの
+fn parse_flags(value: &str) -> Option<u8> {
+    if value.len() == 8 {
+        let mut p = 0;
+        for (i, c) in value.chars().enumerate() {
+            if !"NVUBDIZC".contains(c.to_ascii_uppercase()) {
+                return None;
+            }
+            if c.is_ascii_uppercase() {
+                p |= 0b1000_0000 >> i;
+            }
+        }
+        Some(p)
+    } else {
+        u8::from_str_radix(value, 16).ok()
+    }
+}
+
+/// 最初に食い違った行と、その前後の行
+#[derive(PartialEq, Eq, Debug)]
+pub struct Divergence<'a> {
+    /// 何命令目で食い違ったか(0始まり)
+    pub index: usize,
+    pub fields: Vec<&'static str>,
+    /// (何命令目か, 自分のログの行, 参照ログの行)
+    /// 片方のログが先に終わっている行はNone
+    pub context: Vec<(usize, Option<&'a str>, Option<&'a str>)>,
+}
+
+impl<'a> std::fmt::Display for Divergence<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "diverged at instruction {}: {}",
+            self.index + 1,
+            self.fields.join(", ")
+        )?;
+        for (i, ours, reference) in self.context.iter() {
+            let mark = if *i == self.index { ">" } else { " " };
+            writeln!(f, "{} {:>6} ours: {}", mark, i + 1, ours.unwrap_or("<EOF>"))?;
+            writeln!(
+                f,
+                "{} {:>6} ref:  {}",
+                mark,
+                i + 1,
+                reference.unwrap_or("<EOF>")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 2つのトレースログを命令ごとに揃えて比較し、最初に食い違った位置を返します。
+/// 命令として読めない行(ヘッダなど)は無視します。
+/// 片方のログが先に終わった場合は、対応する行のない最初の行を"EOF"の食い違いとして返します。
+pub fn diff<'a>(ours: &'a str, reference: &'a str, context: usize) -> Option<Divergence<'a>> {
+    let ours = parse_lines(ours);
+    let reference = parse_lines(reference);
+
+    let mismatch = ours
+        .iter()
+        .zip(reference.iter())
+        .map(|((_, l1), (_, l2))| l1.diff(l2))
+        .enumerate()
+        .find(|(_, fields)| !fields.is_empty());
+    let (index, fields) = match mismatch {
+        Some(mismatch) => mismatch,
+        None if ours.len() == reference.len() => return None,
+        None => (ours.len().min(reference.len()), vec!["EOF"]),
+    };
+
+    let end = (index + context + 1).min(ours.len().max(reference.len()));
+    let context = (index.saturating_sub(context)..end)
+        .map(|i| (i, ours.get(i).map(|l| l.0), reference.get(i).map(|l| l.0)))
+        .collect();
+    Some(Divergence {
+        index,
+        fields,
+        context,
+    })
+}
+
+fn parse_lines(log: &str) -> Vec<(&str, TraceLine)> {
+    log.lines()
+        .filter_map(|line| TraceLine::parse(line).map(|t| (line, t)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NESTEST: &str = "\
+C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+C5F5  A2 00     LDX #$00                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 30 CYC:10
+C5F7  86 00     STX $00 = 00                    A:00 X:00 Y:00 P:26 SP:FD PPU:  0, 36 CYC:12
+C5F9  86 10     STX $10 = 00                    A:00 X:00 Y:00 P:26 SP:FD PPU:  0, 45 CYC:15
+C5FB  86 11     STX $11 = 00                    A:00 X:00 Y:00 P:26 SP:FD PPU:  0, 54 CYC:18
+";
+
+    #[test]
+    fn it_parse() {
+        let expect = TraceLine {
+            pc: 0xc000,
+            a: 0x00,
+            x: 0x00,
+            y: 0x00,
+            p: 0x24,
+            sp: 0xfd,
+            cycles: Some(7),
+        };
+        assert_eq!(
+            TraceLine::parse(NESTEST.lines().next().unwrap()),
+            Some(expect)
+        );
+        assert_eq!(
+            TraceLine::parse(
+                "$C000:4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 S:FD P:nvUbdIzc"
+            ),
+            Some(TraceLine {
+                cycles: None,
+                ..expect
+            })
+        );
+        assert_eq!(
+            TraceLine::parse(
+                "C000  JMP $C5F5      A:00 X:00 Y:00 S:FD P:nvUbdIzc   V:0   H:21  Fr:0 Cycle:7"
+            ),
+            Some(expect)
+        );
+        assert_eq!(TraceLine::parse("Trace Log File"), None);
+    }
+
+    #[test]
+    fn it_diff_same() {
+        assert_eq!(diff(NESTEST, NESTEST, 2), None);
+    }
+
+    #[test]
+    fn it_diff_ignore_b_flag() {
+        let ours = NESTEST.replace("P:26", "P:36");
+        assert_eq!(diff(&ours, NESTEST, 2), None);
+    }
+
+    #[test]
+    fn it_diff() {
+        let ours = NESTEST
+            .replace("CYC:12", "CYC:11")
+            .replace("P:26 SP:FD PPU:  0, 36", "P:A6 SP:FD PPU:  0, 36");
+        let divergence = diff(&ours, NESTEST, 1).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.fields, vec!["P", "CYC"]);
+        assert_eq!(
+            divergence
+                .context
+                .iter()
+                .map(|c| c.0)
+                .collect::<Vec<usize>>(),
+            vec![1, 2, 3]
+        );
+        assert!(divergence
+            .to_string()
+            .starts_with("diverged at instruction 3: P, CYC\n"));
+    }
+
+    #[test]
+    fn it_diff_truncated() {
+        // 自分のログが3命令目で終わっている
+        let ours = NESTEST.lines().take(2).collect::<Vec<&str>>().join("\n");
+        let divergence = diff(&ours, NESTEST, 1).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.fields, vec!["EOF"]);
+        let lines = NESTEST.lines().collect::<Vec<&str>>();
+        assert_eq!(
+            divergence.context,
+            vec![
+                (1, Some(lines[1]), Some(lines[1])),
+                (2, None, Some(lines[2])),
+                (3, None, Some(lines[3])),
+            ]
+        );
+        assert!(divergence.to_string().contains("ours: <EOF>"));
+
+        // 参照ログが短い場合も同じ
+        let divergence = diff(NESTEST, &ours, 0).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.context, vec![(2, Some(lines[2]), None)]);
+    }
+}
@@ -0,0 +1,121 @@
+use super::TraceFormat;
+use crate::binary;
+use crate::cpu::{Bus, Cpu};
+use crate::ines::program::{next_in_page, zero_page_indexed, Command, Mode, Program};
+
+/// PCの命令を実行前の状態で逆アセンブルします。
+/// 命令とオペランドはProgramの{:#}の表示を使い、
+/// nestest.logと同じく、実効アドレスとそこに格納されている値も併せて表示します。
+/// 例:
+/// JMP $C5F5
+/// LDA $0200,X @ 0201 = 00
+/// LDA ($80),Y = 0300 @ 0300 = 89
+pub fn disassemble<B: Bus>(cpu: &Cpu<B>, program: &Program, format: TraceFormat) -> String {
+    let f = Formatter(format);
+    let register = &cpu.register;
+    let read = |addr: u16| peek(cpu, addr);
+    let read_u16 = |lower: u16, upper: u16| {
+        binary::u8u8_to_u16(read(upper).unwrap_or(0), read(lower).unwrap_or(0))
+    };
+    // デコード表のアドレッシングモードとオペランドは必ず一致する
+    let operand_addr = program.operand.addr().unwrap_or_default();
+    let text = match (format, program.orderset.cmd) {
+        // nestestではISCをISBと表記する
+        (TraceFormat::Nestest, Command::ISC) => format!("{:#}", program).replacen("ISC", "ISB", 1),
+        _ => format!("{:#}", program),
+    };
+
+    let annotation = match program.orderset.mode {
+        Mode::Implied | Mode::NONE | Mode::Accumulator | Mode::Immediate | Mode::Relative => {
+            String::new()
+        }
+        Mode::ZeroPage => f.value(read(operand_addr)),
+        Mode::ZeroPageX | Mode::ZeroPageY => {
+            let index = if program.orderset.mode == Mode::ZeroPageX {
+                register.X
+            } else {
+                register.Y
+            };
+            let addr = zero_page_indexed(binary::lower_only(operand_addr), index);
+            format!("{}{}", f.at(f.zero_page(addr)), f.value(read(addr)))
+        }
+        Mode::Absolute => match program.orderset.cmd {
+            Command::JMP | Command::JSR => String::new(),
+            _ => f.value(read(operand_addr)),
+        },
+        Mode::AbsoluteX | Mode::AbsoluteY => {
+            let index = if program.orderset.mode == Mode::AbsoluteX {
+                register.X
+            } else {
+                register.Y
+            };
+            let addr = operand_addr.wrapping_add(index as u16);
+            format!("{}{}", f.at(f.absolute(addr)), f.value(read(addr)))
+        }
+        Mode::Indirect => {
+            let target = read_u16(operand_addr, next_in_page(operand_addr));
+            format!(" = {}", f.absolute(target))
+        }
+        Mode::IndirectX => {
+            let pointer = zero_page_indexed(binary::lower_only(operand_addr), register.X);
+            let addr = read_u16(pointer, next_in_page(pointer));
+            format!(
+                "{} = {}{}",
+                f.at(f.zero_page(pointer)),
+                f.absolute(addr),
+                f.value(read(addr))
+            )
+        }
+        Mode::IndirectY => {
+            let pointer = operand_addr;
+            let base = read_u16(pointer, next_in_page(pointer));
+            let addr = base.wrapping_add(register.Y as u16);
+            format!(
+                " = {}{}{}",
+                f.absolute(base),
+                f.at(f.absolute(addr)),
+                f.value(read(addr))
+            )
+        }
+    };
+    text + &annotation
+}
+
+/// I/Oレジスタの値は実行時に読み込む値と一致するとは限らないため、表示しません。
+fn peek<B: Bus>(cpu: &Cpu<B>, addr: u16) -> Option<u8> {
+    match addr {
+        0x2000..=0x401f => None,
+        _ => Some(cpu.bus.peek(addr)),
+    }
+}
+
+/// nestestとFCEUXで異なる、実効アドレスと値の表記
+struct Formatter(TraceFormat);
+
+impl Formatter {
+    fn zero_page(&self, addr: u16) -> String {
+        match self.0 {
+            TraceFormat::Nestest => format!("{:02X}", addr),
+            TraceFormat::Fceux => format!("${:04X}", addr),
+        }
+    }
+
+    fn absolute(&self, addr: u16) -> String {
+        match self.0 {
+            TraceFormat::Nestest => format!("{:04X}", addr),
+            TraceFormat::Fceux => format!("${:04X}", addr),
+        }
+    }
+
+    fn at(&self, addr: String) -> String {
+        format!(" @ {}", addr)
+    }
+
+    fn value(&self, v: Option<u8>) -> String {
+        match (self.0, v) {
+            (_, None) => String::new(),
+            (TraceFormat::Nestest, Some(v)) => format!(" = {:02X}", v),
+            (TraceFormat::Fceux, Some(v)) => format!(" = #${:02X}", v),
+        }
+    }
+}
@@ -0,0 +1,6 @@
+mod diff;
+mod disassemble;
+mod tracer;
+
+pub use diff::diff;
+pub use tracer::{ppu_position, TraceFormat, Tracer};
@@ -0,0 +1,220 @@
+use super::disassemble::disassemble;
+use crate::cpu::{Bus, Cpu};
+use std::io::{Result, Write};
+
+/// トレースログの書式
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TraceFormat {
+    /// nestest.logと同じ書式
+    /// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+    Nestest,
+    /// FCEUX/Mesenのトレースロガーと同じ書式
+    /// $C000:4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 S:FD P:nvUbdIzc
+    Fceux,
+}
+
+/// 命令を実行する前のCpuの状態を1行ずつ書き出します。
+/// Cpu::stepの直前にtraceを呼び出してください。
+pub struct Tracer<W: Write> {
+    out: W,
+    format: TraceFormat,
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn new(out: W, format: TraceFormat) -> Self {
+        Tracer { out, format }
+    }
+
+    /// ppuは(scanline, dot)
+    pub fn trace<B: Bus>(&mut self, cpu: &Cpu<B>, ppu: (u16, u16)) -> Result<()> {
+        writeln!(self.out, "{}", line(cpu, ppu, self.format))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// PPUの位置がわからない場合に、CPUサイクル数から求めます。
+/// PPUはCPUの3倍のクロックで動作し、1ラインは341ドット、1フレームは262ラインです。
+pub fn ppu_position(cycles: u64) -> (u16, u16) {
+    let dots = cycles * 3;
+    ((dots / 341 % 262) as u16, (dots % 341) as u16)
+}
+
+pub fn line<B: Bus>(cpu: &Cpu<B>, ppu: (u16, u16), format: TraceFormat) -> String {
+    let (program, bytes, length) = cpu.peek_program();
+    let register = &cpu.register;
+    let bytes = bytes[..length]
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let text = disassemble(cpu, &program, format);
+    // 非公式命令には*を付ける
+    let star = if program.orderset.official { " " } else { "*" };
+
+    match format {
+        TraceFormat::Nestest => format!(
+            "{:04X}  {:8} {}{:32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:3},{:3} CYC:{}",
+            register.PC,
+            bytes,
+            star,
+            text,
+            register.A,
+            register.X,
+            register.Y,
+            register.P.to_stack(false),
+            register.SP,
+            ppu.0,
+            ppu.1,
+            cpu.cycles
+        ),
+        TraceFormat::Fceux => format!(
+            "${:04X}:{:8} {}{:32}A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:{}",
+            register.PC,
+            bytes,
+            star,
+            text,
+            register.A,
+            register.X,
+            register.Y,
+            register.SP,
+            flags(register.P.to_stack(false))
+        ),
+    }
+}
+
+/// セットされているフラグを大文字で表します。
+fn flags(p: u8) -> String {
+    "NVUBDIZC"
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if p & (0b1000_0000 >> i) != 0 {
+                c
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::fixture;
+    use crate::ines::program::{Operand, Program};
+
+    /// nestestと同じく$C000から実行する
+    fn cpu_with_program(code: &[u8]) -> Cpu {
+        let mut cpu = fixture::cpu_with_program(code);
+        cpu.register.PC = 0xc000;
+        cpu.register.SP = 0xfd;
+        cpu
+    }
+
+    #[test]
+    fn it_nestest() {
+        let cpu = cpu_with_program(&[0x4c, 0xf5, 0xc5]);
+        assert_eq!(
+            line(&cpu, (0, 21), TraceFormat::Nestest),
+            "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+        );
+    }
+
+    #[test]
+    fn it_nestest_effective_address() {
+        let mut cpu = cpu_with_program(&[
+            0xb5, 0x10, // LDA $10,X
+            0xbd, 0x00, 0x02, // LDA $0200,X
+            0xa1, 0x80, // LDA ($80,X)
+            0xb1, 0x89, // LDA ($89),Y
+            0x6c, 0x00, 0x02, // JMP ($0200)
+            0x4a, // LSR A
+            0xd0, 0xfe, // BNE *
+        ]);
+        cpu.register.X = 0x01;
+        cpu.register.Y = 0x01;
+        cpu.bus.write(0x0011, 0x5a).unwrap();
+        cpu.bus.write(0x0081, 0x00).unwrap();
+        cpu.bus.write(0x0082, 0x02).unwrap();
+        cpu.bus.write(0x0089, 0xff).unwrap();
+        cpu.bus.write(0x008a, 0x02).unwrap();
+        cpu.bus.write(0x0200, 0x7e).unwrap();
+        cpu.bus.write(0x0201, 0xdb).unwrap();
+
+        let mut expects = vec![
+            "LDA $10,X @ 11 = 5A",
+            "LDA $0200,X @ 0201 = DB",
+            "LDA ($80,X) @ 81 = 0200 = 7E",
+            "LDA ($89),Y = 02FF @ 0300 = 00",
+            "JMP ($0200) = DB7E",
+            "LSR A",
+            "BNE $C00D",
+        ];
+        expects.reverse();
+        while let Some(expect) = expects.pop() {
+            let (program, _, _) = cpu.peek_program();
+            assert_eq!(disassemble(&cpu, &program, TraceFormat::Nestest), expect);
+            cpu.register.PC += program.orderset.length as u16;
+        }
+    }
+
+    #[test]
+    fn it_fceux() {
+        let mut cpu = cpu_with_program(&[0xbd, 0x00, 0x02]);
+        cpu.register.X = 0x01;
+        assert_eq!(
+            line(&cpu, (0, 21), TraceFormat::Fceux),
+            "$C000:BD 00 02  LDA $0200,X @ $0201 = #$00      A:00 X:01 Y:00 S:FD P:nvUbdIzc"
+        );
+    }
+
+    #[test]
+    fn it_nestest_unofficial() {
+        let mut cpu = cpu_with_program(&[
+            0x04, 0xa9, // NOP $A9
+            0xe3, 0x45, // ISB ($45,X)
+        ]);
+        cpu.register.A = 0xaa;
+        assert_eq!(
+            line(&cpu, (0, 21), TraceFormat::Nestest),
+            "C000  04 A9    *NOP $A9 = 00                    A:AA X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"
+        );
+        cpu.step().unwrap();
+        let (program, _, _) = cpu.peek_program();
+        assert_eq!(
+            disassemble(&cpu, &program, TraceFormat::Nestest),
+            "ISB ($45,X) @ 45 = 0000 = 00"
+        );
+    }
+
+    #[test]
+    fn it_disassemble_unexpected_operand() {
+        let cpu = cpu_with_program(&[]);
+        // 即値にアドレスが入っていてもpanicしない
+        let mut program = Program::parse(&[0xa9, 0x44], 0);
+        program.operand = Operand::Addr(0x0044);
+        assert_eq!(
+            disassemble(&cpu, &program, TraceFormat::Nestest),
+            "LDA #$44"
+        );
+    }
+
+    #[test]
+    fn it_tracer() {
+        let mut cpu = cpu_with_program(&[0xa9, 0x00, 0xea]);
+        let mut tracer = Tracer::new(vec![], TraceFormat::Nestest);
+        for _ in 0..2 {
+            tracer.trace(&cpu, ppu_position(cpu.cycles)).unwrap();
+            cpu.step().unwrap();
+        }
+        let log = String::from_utf8(tracer.into_inner()).unwrap();
+        assert_eq!(
+            log,
+            "C000  A9 00     LDA #$00                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7\n\
+             C002  EA        NOP                             A:00 X:00 Y:00 P:26 SP:FD PPU:  0, 27 CYC:9\n"
+        );
+    }
+}